@@ -0,0 +1,82 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use day1::calibrate;
+use day1::lexer::{Lexer, Vocabulary};
+use std::hint::black_box;
+
+const INPUT: &str = include_str!("../src/input.txt");
+
+const NUMBERS: [&[u8]; 9] = [
+    b"one", b"two", b"three", b"four", b"five", b"six", b"seven", b"eight", b"nine",
+];
+
+// The original lexer: try every word at every position and collect everything
+fn naive_lex(s: &str) -> Vec<u32> {
+    let mut res: Vec<u32> = vec![];
+
+    let bytes = s.as_bytes();
+
+    let mut pos: usize = 0;
+    while pos < bytes.len() {
+        let remaining = &bytes[pos..];
+
+        if bytes[pos].is_ascii_digit() {
+            res.push((bytes[pos] - b'0') as u32);
+        }
+
+        for (i, number) in NUMBERS.iter().enumerate() {
+            if remaining.starts_with(number) {
+                res.push(i as u32 + 1);
+                break;
+            }
+        }
+        pos += 1;
+    }
+
+    res
+}
+
+fn calibrate_all(numbers: &[u32]) -> u32 {
+    numbers.first().unwrap() * 10 + numbers.last().unwrap()
+}
+
+fn bench_calibrate(c: &mut Criterion) {
+    let lines: Vec<_> = INPUT.lines().filter(|line| !line.is_empty()).collect();
+    let lexer = Lexer::new(&Vocabulary::english());
+
+    let mut group = c.benchmark_group("calibrate");
+    group.bench_function("naive lex and collect", |b| {
+        b.iter(|| -> u32 {
+            lines
+                .iter()
+                .map(|line| calibrate_all(&naive_lex(black_box(line))))
+                .sum()
+        })
+    });
+    group.bench_function("lex and collect", |b| {
+        b.iter(|| -> u32 {
+            lines
+                .iter()
+                .map(|line| {
+                    let numbers: Vec<_> = lexer
+                        .lex(black_box(line))
+                        .iter()
+                        .map(|token| token.value)
+                        .collect();
+                    calibrate_all(&numbers)
+                })
+                .sum()
+        })
+    });
+    group.bench_function("first and last", |b| {
+        b.iter(|| -> u32 {
+            lines
+                .iter()
+                .map(|line| calibrate(&lexer, black_box(line)).unwrap())
+                .sum()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_calibrate);
+criterion_main!(benches);
@@ -0,0 +1,245 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+
+const ROOT: usize = 0;
+
+/// A match of `patterns[pattern]` at `span` of the haystack
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Match {
+    pub pattern: usize,
+    pub span: Range<usize>,
+}
+
+/// Multi-pattern matcher that finds all (overlapping) occurrences of a set of patterns in one
+/// pass over the haystack.
+///
+/// The trie is compiled into a full transition table (failure links are folded in at build
+/// time), so every byte of the haystack costs exactly one table lookup.
+pub struct AhoCorasick {
+    /// Bytes that appear in no pattern all behave the same, so they share class 0.
+    /// Every other byte gets its own class, which keeps the transition table narrow
+    byte_classes: [usize; 256],
+    class_count: usize,
+    /// Next state is `transitions[state * class_count + class]`
+    transitions: Vec<u32>,
+    /// Patterns ending at each state, including the ones reached through failure links
+    outputs: Vec<Vec<usize>>,
+    pattern_lengths: Vec<usize>,
+    max_pattern_len: usize,
+    /// Whether a pattern occurs in another one other than as a suffix, in which case a match that
+    /// ends later can start earlier
+    has_inner_patterns: bool,
+}
+
+impl AhoCorasick {
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Self {
+        let mut transitions: Vec<[Option<usize>; 256]> = vec![[None; 256]];
+        let mut outputs: Vec<Vec<usize>> = vec![vec![]];
+
+        // Build the trie
+        for (pattern_index, pattern) in patterns.iter().enumerate() {
+            let mut state = ROOT;
+            for &byte in pattern.as_ref() {
+                state = match transitions[state][byte as usize] {
+                    Some(next) => next,
+                    None => {
+                        transitions.push([None; 256]);
+                        outputs.push(vec![]);
+                        let next = transitions.len() - 1;
+                        transitions[state][byte as usize] = Some(next);
+                        next
+                    }
+                };
+            }
+            outputs[state].push(pattern_index);
+        }
+
+        // Breadth-first so that the failure state of a node is always resolved before the node
+        let mut resolved = vec![[ROOT; 256]; transitions.len()];
+        let mut failure = vec![ROOT; transitions.len()];
+        let mut queue = VecDeque::new();
+        for byte in 0..256 {
+            if let Some(child) = transitions[ROOT][byte] {
+                resolved[ROOT][byte] = child;
+                queue.push_back(child);
+            }
+        }
+        while let Some(state) = queue.pop_front() {
+            let inherited = outputs[failure[state]].clone();
+            outputs[state].extend(inherited);
+            outputs[state].sort_unstable();
+
+            for byte in 0..256 {
+                match transitions[state][byte] {
+                    Some(child) => {
+                        failure[child] = resolved[failure[state]][byte];
+                        resolved[state][byte] = child;
+                        queue.push_back(child);
+                    }
+                    None => resolved[state][byte] = resolved[failure[state]][byte],
+                }
+            }
+        }
+
+        let mut byte_classes = [0; 256];
+        let mut class_representatives = vec![None];
+        for byte in 0..256 {
+            if transitions.iter().any(|row| row[byte].is_some()) {
+                byte_classes[byte] = class_representatives.len();
+                class_representatives.push(Some(byte));
+            }
+        }
+        // Any byte that is not in a pattern can stand for class 0
+        class_representatives[0] = (0..256).find(|&byte| byte_classes[byte] == 0);
+
+        let class_count = class_representatives.len();
+        let mut compressed = vec![ROOT as u32; resolved.len() * class_count];
+        for (state, row) in resolved.iter().enumerate() {
+            for (class, representative) in class_representatives.iter().enumerate() {
+                if let Some(byte) = representative {
+                    compressed[state * class_count + class] = row[*byte] as u32;
+                }
+            }
+        }
+
+        let pattern_lengths: Vec<_> = patterns.iter().map(|p| p.as_ref().len()).collect();
+        let mut automaton = AhoCorasick {
+            byte_classes,
+            class_count,
+            transitions: compressed,
+            outputs,
+            max_pattern_len: pattern_lengths.iter().copied().max().unwrap_or(0),
+            pattern_lengths,
+            has_inner_patterns: false,
+        };
+        automaton.has_inner_patterns = patterns.iter().any(|pattern| {
+            let pattern = pattern.as_ref();
+            automaton
+                .find_overlapping(pattern)
+                .any(|m| m.span.end < pattern.len())
+        });
+        automaton
+    }
+
+    pub fn max_pattern_len(&self) -> usize {
+        self.max_pattern_len
+    }
+
+    pub fn has_inner_patterns(&self) -> bool {
+        self.has_inner_patterns
+    }
+
+    fn next_state(&self, state: usize, byte: u8) -> usize {
+        self.transitions[state * self.class_count + self.byte_classes[byte as usize]] as usize
+    }
+
+    /// End of the first match and the patterns ending there, sorted by index.
+    /// Unlike `find_overlapping`, nothing past that end is looked at
+    pub fn find_first_end<'a, I: IntoIterator<Item = &'a u8>>(
+        &self,
+        haystack: I,
+    ) -> Option<(usize, &[usize])> {
+        let mut state = ROOT;
+        for (i, &byte) in haystack.into_iter().enumerate() {
+            state = self.next_state(state, byte);
+            if !self.outputs[state].is_empty() {
+                return Some((i + 1, &self.outputs[state]));
+            }
+        }
+        None
+    }
+
+    pub fn pattern_len(&self, pattern: usize) -> usize {
+        self.pattern_lengths[pattern]
+    }
+
+    /// All matches, ordered by end position and then by pattern index.
+    /// Matches are found lazily, so stopping early skips the rest of the haystack
+    pub fn find_overlapping<'a, I: IntoIterator<Item = &'a u8>>(
+        &'a self,
+        haystack: I,
+    ) -> FindOverlapping<'a, I::IntoIter> {
+        FindOverlapping {
+            automaton: self,
+            haystack: haystack.into_iter(),
+            state: ROOT,
+            end: 0,
+            output_index: 0,
+        }
+    }
+}
+
+pub struct FindOverlapping<'a, I> {
+    automaton: &'a AhoCorasick,
+    haystack: I,
+    state: usize,
+    end: usize,
+    /// Next output of `state` to report
+    output_index: usize,
+}
+
+impl<'a, I: Iterator<Item = &'a u8>> Iterator for FindOverlapping<'a, I> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let automaton = self.automaton;
+        while self.output_index >= automaton.outputs[self.state].len() {
+            self.state = automaton.next_state(self.state, *self.haystack.next()?);
+            self.end += 1;
+            self.output_index = 0;
+        }
+
+        let pattern = automaton.outputs[self.state][self.output_index];
+        self.output_index += 1;
+        Some(Match {
+            pattern,
+            span: self.end - automaton.pattern_lengths[pattern]..self.end,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_overlapping() {
+        let automaton = AhoCorasick::new(&["he", "she", "his", "hers"]);
+        let matches: Vec<_> = automaton.find_overlapping(b"ushers").collect();
+        assert_eq!(
+            matches,
+            vec![
+                Match {
+                    pattern: 0,
+                    span: 2..4
+                },
+                Match {
+                    pattern: 1,
+                    span: 1..4
+                },
+                Match {
+                    pattern: 3,
+                    span: 2..6
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_first_end() {
+        let automaton = AhoCorasick::new(&["he", "she", "his", "hers"]);
+        assert_eq!(automaton.find_first_end(b"ushers"), Some((4, &[0, 1][..])));
+        assert_eq!(automaton.find_first_end(b"xyz"), None);
+        assert!(automaton.has_inner_patterns());
+        // Only suffixes
+        assert!(!AhoCorasick::new(&["she", "he", "e"]).has_inner_patterns());
+        assert!(AhoCorasick::new(&["sixteen", "six"]).has_inner_patterns());
+        assert!(AhoCorasick::new(&["sixteen", "xt"]).has_inner_patterns());
+    }
+
+    #[test]
+    fn test_no_patterns() {
+        let automaton = AhoCorasick::new::<&str>(&[]);
+        assert_eq!(automaton.find_overlapping(b"abc").count(), 0);
+    }
+}
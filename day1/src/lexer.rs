@@ -0,0 +1,274 @@
+use crate::aho_corasick::{AhoCorasick, Match};
+use std::ops::Range;
+
+/// Number words and the values they stand for
+#[derive(Clone, Debug)]
+pub struct Vocabulary {
+    words: Vec<(String, u32)>,
+}
+
+impl Vocabulary {
+    pub fn new(words: &[(&str, u32)]) -> Self {
+        Vocabulary {
+            words: words
+                .iter()
+                .map(|&(word, value)| (word.to_owned(), value))
+                .collect(),
+        }
+    }
+
+    /// Only the digits `0` to `9`, without any words
+    pub fn digits() -> Self {
+        Vocabulary { words: vec![] }
+    }
+
+    pub fn english() -> Self {
+        Vocabulary::new(&[
+            ("one", 1),
+            ("two", 2),
+            ("three", 3),
+            ("four", 4),
+            ("five", 5),
+            ("six", 6),
+            ("seven", 7),
+            ("eight", 8),
+            ("nine", 9),
+        ])
+    }
+
+    pub fn german() -> Self {
+        Vocabulary::new(&[
+            ("eins", 1),
+            ("zwei", 2),
+            ("drei", 3),
+            ("vier", 4),
+            ("fünf", 5),
+            ("sechs", 6),
+            ("sieben", 7),
+            ("acht", 8),
+            ("neun", 9),
+        ])
+    }
+
+    pub fn french() -> Self {
+        Vocabulary::new(&[
+            ("un", 1),
+            ("deux", 2),
+            ("trois", 3),
+            ("quatre", 4),
+            ("cinq", 5),
+            ("six", 6),
+            ("sept", 7),
+            ("huit", 8),
+            ("neuf", 9),
+        ])
+    }
+
+    /// Add a word, e.g. `with("zero", 0)` or `with("ten", 10)`
+    pub fn with(mut self, word: &str, value: u32) -> Self {
+        self.words.push((word.to_owned(), value));
+        self
+    }
+
+    /// Words of both vocabularies
+    pub fn merge(mut self, other: &Vocabulary) -> Self {
+        self.words.extend(other.words.iter().cloned());
+        self
+    }
+}
+
+/// A digit or number word found in a line
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Token {
+    pub value: u32,
+    pub span: Range<usize>,
+}
+
+pub struct Lexer {
+    automaton: AhoCorasick,
+    /// Matches the reversed patterns on the reversed line, for scanning from the end
+    reversed_automaton: AhoCorasick,
+    values: Vec<u32>,
+}
+
+impl Lexer {
+    /// Lexer recognizing the digits `0` to `9` plus every word of `vocabulary`
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        let digits = (0..10).map(|digit: u32| (digit.to_string(), digit));
+        let (patterns, values): (Vec<_>, Vec<_>) =
+            digits.chain(vocabulary.words.iter().cloned()).unzip();
+
+        let reversed_patterns: Vec<Vec<u8>> = patterns
+            .iter()
+            .map(|pattern| pattern.bytes().rev().collect())
+            .collect();
+
+        Lexer {
+            automaton: AhoCorasick::new(&patterns),
+            reversed_automaton: AhoCorasick::new(&reversed_patterns),
+            values,
+        }
+    }
+
+    /// The token starting first, same as `lex(s).first()` but without lexing the whole line
+    pub fn first(&self, s: &str) -> Option<Token> {
+        if !self.automaton.has_inner_patterns() {
+            // Every match ending later also starts later, so the first token ends first
+            let (end, patterns) = self.automaton.find_first_end(s.as_bytes())?;
+            let pattern = *patterns
+                .iter()
+                .min_by_key(|&&pattern| (end - self.automaton.pattern_len(pattern), pattern))?;
+            return Some(self.token(pattern, end - self.automaton.pattern_len(pattern)..end));
+        }
+
+        // Matches are found by end position, so a longer word that starts earlier can still show
+        // up after the first match. Keep scanning until no such word can exist.
+        let max_len = self.automaton.max_pattern_len();
+        let mut best: Option<Match> = None;
+        for m in self.automaton.find_overlapping(s.as_bytes()) {
+            if let Some(best) = &best {
+                if m.span.end > best.span.start + max_len {
+                    break;
+                }
+            }
+            if best
+                .as_ref()
+                .is_none_or(|best| (m.span.start, m.pattern) < (best.span.start, best.pattern))
+            {
+                best = Some(m);
+            }
+        }
+
+        best.map(|m| self.token(m.pattern, m.span))
+    }
+
+    /// The token starting last, same as `lex(s).last()` but only scanning from the end
+    pub fn last(&self, s: &str) -> Option<Token> {
+        // On the reversed line, matches are found by their original start position from the back,
+        // so the first match is the last token
+        let bytes = s.as_bytes();
+        let (end, patterns) = self.reversed_automaton.find_first_end(bytes.iter().rev())?;
+        // Ties at the same position come by pattern index, and `lex` puts the largest one last
+        let pattern = *patterns.last()?;
+        let start = bytes.len() - end;
+        Some(self.token(pattern, start..start + self.automaton.pattern_len(pattern)))
+    }
+
+    fn token(&self, pattern: usize, span: Range<usize>) -> Token {
+        Token {
+            value: self.values[pattern],
+            span,
+        }
+    }
+
+    /// All tokens of `s` ordered by start position. Tokens can overlap (e.g. `twone`)
+    pub fn lex(&self, s: &str) -> Vec<Token> {
+        let mut matches: Vec<_> = self.automaton.find_overlapping(s.as_bytes()).collect();
+        matches.sort_by_key(|m| (m.span.start, m.pattern));
+        matches
+            .into_iter()
+            .map(|m| self.token(m.pattern, m.span))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(lexer: &Lexer, s: &str) -> Vec<u32> {
+        lexer.lex(s).iter().map(|token| token.value).collect()
+    }
+
+    #[test]
+    fn test_english() {
+        let lexer = Lexer::new(&Vocabulary::english());
+        assert_eq!(values(&lexer, "two1nine"), vec![2, 1, 9]);
+        assert_eq!(values(&lexer, "xtwone3four"), vec![2, 1, 3, 4]);
+        assert_eq!(values(&lexer, "zoneight234"), vec![1, 8, 2, 3, 4]);
+        assert_eq!(
+            lexer.lex("7pqrstsixteen"),
+            vec![
+                Token {
+                    value: 7,
+                    span: 0..1
+                },
+                Token {
+                    value: 6,
+                    span: 6..9
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_first_and_last() {
+        let lexer = Lexer::new(&Vocabulary::english());
+        // No lookahead needed, unlike `sixteen` below
+        assert!(!lexer.automaton.has_inner_patterns());
+        for line in [
+            "two1nine",
+            "eightwothree",
+            "abcone2threexyz",
+            "xtwone3four",
+            "4nineeightseven2",
+            "zoneight234",
+            "7pqrstsixteen",
+            "twone",
+            "oneight",
+            "8",
+            "nothing",
+            "",
+        ] {
+            let tokens = lexer.lex(line);
+            assert_eq!(lexer.first(line).as_ref(), tokens.first(), "{line}");
+            assert_eq!(lexer.last(line).as_ref(), tokens.last(), "{line}");
+        }
+        assert_eq!(lexer.first("twone").unwrap().value, 2);
+        assert_eq!(lexer.last("twone").unwrap().value, 1);
+    }
+
+    #[test]
+    fn test_first_prefers_earlier_start() {
+        // "sixteen" ends after "six" and "teen" but starts first
+        let lexer = Lexer::new(&Vocabulary::digits().with("teen", 0).with("sixteen", 16));
+        assert_eq!(
+            lexer.first("sixteen"),
+            Some(Token {
+                value: 16,
+                span: 0..7
+            })
+        );
+        assert_eq!(lexer.last("sixteen").unwrap().value, 0);
+        assert_eq!(
+            lexer.lex("sixteen").first(),
+            lexer.first("sixteen").as_ref()
+        );
+
+        let lexer = Lexer::new(&Vocabulary::english().with("sixteen", 16));
+        for line in ["sixteen", "1sixteen", "sixteen1six", "sixsixteen"] {
+            let tokens = lexer.lex(line);
+            assert_eq!(lexer.first(line).as_ref(), tokens.first(), "{line}");
+            assert_eq!(lexer.last(line).as_ref(), tokens.last(), "{line}");
+        }
+    }
+
+    #[test]
+    fn test_digits_only() {
+        let lexer = Lexer::new(&Vocabulary::digits());
+        assert_eq!(values(&lexer, "a1b2c3d4e5f"), vec![1, 2, 3, 4, 5]);
+        assert_eq!(values(&lexer, "treb7uchet0"), vec![7, 0]);
+    }
+
+    #[test]
+    fn test_other_vocabularies() {
+        let lexer = Lexer::new(&Vocabulary::german());
+        assert_eq!(values(&lexer, "zweinsfünfx"), vec![2, 1, 5]);
+
+        let lexer = Lexer::new(&Vocabulary::french().merge(&Vocabulary::english()));
+        assert_eq!(values(&lexer, "troiseven"), vec![3, 7]);
+
+        let lexer = Lexer::new(&Vocabulary::english().with("zero", 0).with("ten", 10));
+        assert_eq!(values(&lexer, "zerotenine"), vec![0, 10, 9]);
+    }
+}
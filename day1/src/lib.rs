@@ -0,0 +1,152 @@
+pub mod aho_corasick;
+pub mod lexer;
+
+use crate::lexer::Lexer;
+
+/// A line without any digit or number word, so it has no calibration value
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct NoDigitError {
+    /// 1-based
+    pub line_number: usize,
+}
+
+impl std::fmt::Display for NoDigitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: no digit found", self.line_number)
+    }
+}
+
+impl std::error::Error for NoDigitError {}
+
+/// Total of the lines that could be calibrated, and the ones that were skipped
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CalibrationSummary {
+    pub total: u32,
+    pub calibrated_count: usize,
+    pub skipped: Vec<NoDigitError>,
+}
+
+impl std::fmt::Display for CalibrationSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({} lines calibrated, {} skipped)",
+            self.total,
+            self.calibrated_count,
+            self.skipped.len()
+        )
+    }
+}
+
+/// First and last digit of the line combined into a two-digit value. Number words worth 10 or
+/// more count as the digits they are written with, so `ten1` calibrates to 11 like `101` does
+pub fn calibrate(lexer: &Lexer, line: &str) -> Option<u32> {
+    let first = lexer.first(line)?.value;
+    let last = lexer.last(line)?.value;
+    Some(leading_digit(first) * 10 + last % 10)
+}
+
+fn leading_digit(mut value: u32) -> u32 {
+    while value >= 10 {
+        value /= 10;
+    }
+    value
+}
+
+/// Calibration value of every non-empty line of `input`
+pub fn calibrate_lines<'a>(
+    lexer: &'a Lexer,
+    input: &'a str,
+) -> impl Iterator<Item = Result<u32, NoDigitError>> + 'a {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| calibrate(lexer, line).ok_or(NoDigitError { line_number: i + 1 }))
+}
+
+/// Sum of the calibration values, skipping lines without digits
+pub fn calibrate_lenient(lexer: &Lexer, input: &str) -> CalibrationSummary {
+    calibrate_lines(lexer, input).fold(CalibrationSummary::default(), |mut summary, result| {
+        match result {
+            Ok(value) => {
+                summary.total += value;
+                summary.calibrated_count += 1;
+            }
+            Err(error) => summary.skipped.push(error),
+        }
+        summary
+    })
+}
+
+/// Sum of the calibration values, failing at the first line without digits
+pub fn calibrate_strict(lexer: &Lexer, input: &str) -> Result<u32, NoDigitError> {
+    calibrate_lines(lexer, input).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Vocabulary;
+
+    const INPUT: &str = "two1nine
+eightwothree
+nothing here
+
+abcone2threexyz
+xyz
+";
+
+    #[test]
+    fn test_calibrate_lines() {
+        let lexer = Lexer::new(&Vocabulary::english());
+        let results: Vec<_> = calibrate_lines(&lexer, INPUT).collect();
+        assert_eq!(
+            results,
+            vec![
+                Ok(29),
+                Ok(83),
+                Err(NoDigitError { line_number: 3 }),
+                Ok(13),
+                Err(NoDigitError { line_number: 6 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lenient_and_strict() {
+        let lexer = Lexer::new(&Vocabulary::english());
+        let summary = calibrate_lenient(&lexer, INPUT);
+        assert_eq!(summary.total, 125);
+        assert_eq!(summary.calibrated_count, 3);
+        assert_eq!(
+            summary.skipped,
+            vec![
+                NoDigitError { line_number: 3 },
+                NoDigitError { line_number: 6 }
+            ]
+        );
+        assert_eq!(summary.to_string(), "125 (3 lines calibrated, 2 skipped)");
+
+        assert_eq!(
+            calibrate_strict(&lexer, INPUT),
+            Err(NoDigitError { line_number: 3 })
+        );
+        assert_eq!(
+            calibrate_strict(&lexer, "two1nine\n\n4nineeightseven2\n"),
+            Ok(71)
+        );
+    }
+
+    #[test]
+    fn test_values_past_nine() {
+        let lexer = Lexer::new(&Vocabulary::english().with("ten", 10).with("max", u32::MAX));
+        assert_eq!(calibrate(&lexer, "ten1"), calibrate(&lexer, "101"));
+        assert_eq!(calibrate(&lexer, "ten1"), Some(11));
+        assert_eq!(calibrate(&lexer, "3ten"), Some(30));
+        assert_eq!(calibrate(&lexer, "ten"), Some(10));
+        // 4294967295
+        assert_eq!(calibrate(&lexer, "max"), Some(45));
+        assert_eq!(calibrate(&lexer, "maxmax"), Some(45));
+    }
+}
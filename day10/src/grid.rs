@@ -1,5 +1,9 @@
+#![allow(dead_code)]
+
 type Point = (isize, isize);
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "GridRepr<T>"))]
 pub struct Grid<T> {
     pub width: isize,
     pub height: isize,
@@ -7,7 +11,7 @@ pub struct Grid<T> {
 }
 
 impl<T> Grid<T> {
-    fn is_out_of_bound(self: &Self, (x, y): Point) -> bool {
+    fn is_out_of_bound(&self, (x, y): Point) -> bool {
         x < 0 || x >= self.width || y < 0 || y >= self.height
     }
 
@@ -26,6 +30,73 @@ impl<T> Grid<T> {
             Some(&mut self.data[(y * self.width + x) as usize])
         }
     }
+
+    fn point_of_index(&self, index: usize) -> Point {
+        (index as isize % self.width, index as isize / self.width)
+    }
+
+    /// Build a grid of the same size by applying `f` to every cell
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            data: self.data.iter().map(f).collect(),
+        }
+    }
+
+    /// Combine two grids of the same size cell by cell
+    pub fn zip_with<U, V>(&self, other: &Grid<U>, mut f: impl FnMut(&T, &U) -> V) -> Grid<V> {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Grid sizes mismatch"
+        );
+
+        Grid {
+            width: self.width,
+            height: self.height,
+            data: self
+                .data
+                .iter()
+                .zip(other.data.iter())
+                .map(|(lhs, rhs)| f(lhs, rhs))
+                .collect(),
+        }
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Points where the two grids of the same size differ, in row-major order
+    pub fn diff(&self, other: &Self) -> Vec<Point> {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Grid sizes mismatch"
+        );
+
+        self.data
+            .iter()
+            .zip(other.data.iter())
+            .enumerate()
+            .filter(|(_, (lhs, rhs))| lhs != rhs)
+            .map(|(i, _)| self.point_of_index(i))
+            .collect()
+    }
+
+    /// Number of cells where the two grids of the same size differ
+    pub fn hamming_distance(&self, other: &Self) -> usize {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Grid sizes mismatch"
+        );
+
+        self.data
+            .iter()
+            .zip(other.data.iter())
+            .filter(|(lhs, rhs)| lhs != rhs)
+            .count()
+    }
 }
 
 impl<T: Clone + Default> Grid<T> {
@@ -54,7 +125,7 @@ impl<T: Clone + Default> Grid<T> {
         }
     }
 
-    pub fn rows(self: &Self) -> Vec<&[T]> {
+    pub fn rows(&self) -> Vec<&[T]> {
         let mut res = vec![];
         for y in 0..self.height {
             let begin = (y * self.width) as usize;
@@ -65,6 +136,141 @@ impl<T: Clone + Default> Grid<T> {
     }
 }
 
+impl Grid<u8> {
+    /// Encode a character grid as run-length text, one line per row.
+    /// A run is written as `<count><char>`, with the count omitted for single cells.
+    /// e.g. `OO.#..` becomes `2O.#2.`
+    ///
+    /// Digits and `\\` are escaped with a `\\`, and line breaks are written as `\\n` and `\\r`,
+    /// so any grid can be encoded. e.g. `1112..` becomes `3\\1\\22.`
+    pub fn to_rle(&self) -> String {
+        let mut result = String::new();
+        for row in self.rows() {
+            for run in row.chunk_by(|lhs, rhs| lhs == rhs) {
+                if run.len() > 1 {
+                    result += &run.len().to_string();
+                }
+                match run[0] {
+                    b'\n' => result += "\\n",
+                    b'\r' => result += "\\r",
+                    c @ (b'0'..=b'9' | b'\\') => {
+                        result.push('\\');
+                        result.push(c as char);
+                    }
+                    c => result.push(c as char),
+                }
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Decode a grid written by [`Grid::to_rle`]. Returns `None` on malformed or ragged input,
+    /// including rows longer than the first one or than `MAX_RLE_WIDTH`
+    pub fn from_rle(input: &str) -> Option<Grid<u8>> {
+        const MAX_RLE_WIDTH: usize = 1 << 16;
+
+        let mut rows: Vec<Vec<u8>> = vec![];
+        for line in input.lines() {
+            let max_width = rows.first().map_or(MAX_RLE_WIDTH, |row| row.len());
+            let mut row = vec![];
+            let mut count: Option<usize> = None;
+            let mut chars = line.chars();
+            while let Some(c) = chars.next() {
+                let c = match c {
+                    '0'..='9' => {
+                        let digit = c as usize - '0' as usize;
+                        count = Some(count.unwrap_or(0).checked_mul(10)?.checked_add(digit)?);
+                        if count? > max_width {
+                            return None;
+                        }
+                        continue;
+                    }
+                    '\\' => match chars.next()? {
+                        'n' => b'\n',
+                        'r' => b'\r',
+                        c @ ('0'..='9' | '\\') => c as u8,
+                        _ => return None,
+                    },
+                    // Cells are bytes, which `to_rle` writes as chars up to U+00FF
+                    c => u8::try_from(c).ok()?,
+                };
+                let count = count.take().unwrap_or(1);
+                if row.len() + count > max_width {
+                    return None;
+                }
+                row.extend(std::iter::repeat_n(c, count));
+            }
+            // A trailing count without a character
+            if count.is_some() {
+                return None;
+            }
+            rows.push(row);
+        }
+
+        let width = rows.first()?.len();
+        rows.iter()
+            .all(|row| row.len() == width)
+            .then(|| Grid::from_nested(&rows))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> Grid<T> {
+    /// Binary snapshot of the grid for debugging
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serde::encode_to_vec(self, bincode::config::standard()).unwrap()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::de::DeserializeOwned> Grid<T> {
+    /// Load a snapshot written by [`Grid::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Grid<T>> {
+        bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+            .ok()
+            .map(|(grid, _)| grid)
+    }
+}
+
+// Deserialized grids go through here so that the size always matches the data
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct GridRepr<T> {
+    width: isize,
+    height: isize,
+    data: Box<[T]>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<GridRepr<T>> for Grid<T> {
+    type Error = String;
+
+    fn try_from(
+        GridRepr {
+            width,
+            height,
+            data,
+        }: GridRepr<T>,
+    ) -> Result<Self, Self::Error> {
+        let cell_count = width
+            .checked_mul(height)
+            .and_then(|cell_count| usize::try_from(cell_count).ok());
+        if width < 0 || height < 0 || cell_count != Some(data.len()) {
+            Err(format!(
+                "{width}x{height} grid can't hold {} cells",
+                data.len()
+            ))
+        } else {
+            Ok(Grid {
+                width,
+                height,
+                data,
+            })
+        }
+    }
+}
+
 impl<T> std::ops::Index<Point> for Grid<T> {
     type Output = T;
 
@@ -78,3 +284,49 @@ impl<T> std::ops::IndexMut<Point> for Grid<T> {
         self.get_mut(point).unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Grid<u8> {
+        let nested: Vec<_> = input.lines().map(|line| line.as_bytes().to_vec()).collect();
+        Grid::from_nested(&nested)
+    }
+
+    #[test]
+    fn test_map() {
+        let grid = parse("S-7\n|.|\nL-J");
+        let distances = grid.map(|&c| (c == b'S').then_some(0));
+        assert_eq!((distances.width, distances.height), (3, 3));
+        assert_eq!(distances[(0, 0)], Some(0));
+        assert_eq!(distances[(1, 0)], None);
+        assert_eq!(distances[(2, 2)], None);
+    }
+
+    #[test]
+    fn test_zip_with() {
+        let grid = parse("S-\n|.");
+        let on_loop = grid.map(|&c| c != b'.');
+        let zipped = grid.zip_with(&on_loop, |&c, &on_loop| (c, on_loop));
+        assert_eq!(zipped[(0, 0)], (b'S', true));
+        assert_eq!(zipped[(1, 1)], (b'.', false));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zip_with_size_mismatch() {
+        parse("S-\n|.").zip_with(&parse("S-."), |_, _| ());
+    }
+
+    #[test]
+    fn test_diff_and_hamming_distance() {
+        let first = parse("S-7\n|.|\nL-J");
+        let second = parse("S-7\n|I|\nL-7");
+        assert_eq!(first.diff(&first), vec![]);
+        assert_eq!(first.diff(&second), vec![(1, 1), (2, 2)]);
+        assert_eq!(first.hamming_distance(&first), 0);
+        assert_eq!(first.hamming_distance(&second), 2);
+        assert_eq!(second.hamming_distance(&first), 2);
+    }
+}
@@ -0,0 +1,209 @@
+#![allow(dead_code)]
+
+use crate::grid::Grid;
+use std::io::Write;
+
+type Point = (isize, isize);
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const RED: Rgb = Rgb(255, 0, 0);
+    pub const GREEN: Rgb = Rgb(0, 255, 0);
+    pub const BLUE: Rgb = Rgb(0, 0, 255);
+    pub const YELLOW: Rgb = Rgb(255, 255, 0);
+    pub const GRAY: Rgb = Rgb(128, 128, 128);
+}
+
+/// A rendered grid where every cell is a `scale`×`scale` block of pixels
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    scale: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn from_grid<T>(grid: &Grid<T>, scale: usize, palette: impl Fn(&T) -> Rgb) -> Image {
+        assert!(scale > 0, "Scale should be at least 1");
+
+        let width = grid.width as usize * scale;
+        let height = grid.height as usize * scale;
+        let mut pixels = vec![Rgb::default(); width * height];
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                let color = palette(&grid[(x, y)]);
+                let (x, y) = (x as usize, y as usize);
+                for pixel_y in y * scale..(y + 1) * scale {
+                    pixels[pixel_y * width + x * scale..pixel_y * width + (x + 1) * scale]
+                        .fill(color);
+                }
+            }
+        }
+
+        Image {
+            width,
+            height,
+            scale,
+            pixels,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    fn fill_square(&mut self, (center_x, center_y): (isize, isize), radius: isize, color: Rgb) {
+        for y in center_y - radius..=center_y + radius {
+            for x in center_x - radius..=center_x + radius {
+                if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+                    self.pixels[y as usize * self.width + x as usize] = color;
+                }
+            }
+        }
+    }
+
+    /// Draw a polyline through the centers of the cells in `path`
+    pub fn overlay_path(&mut self, path: &[Point], color: Rgb) {
+        let scale = self.scale as isize;
+        let radius = scale / 8;
+        let center = |(x, y): Point| (x * scale + scale / 2, y * scale + scale / 2);
+
+        if let [point] = path {
+            self.fill_square(center(*point), radius, color);
+        }
+        for segment in path.windows(2) {
+            let (from_x, from_y) = center(segment[0]);
+            let (to_x, to_y) = center(segment[1]);
+            let steps = (to_x - from_x).abs().max((to_y - from_y).abs()).max(1);
+            for i in 0..=steps {
+                let x = from_x + (to_x - from_x) * i / steps;
+                let y = from_y + (to_y - from_y) * i / steps;
+                self.fill_square((x, y), radius, color);
+            }
+        }
+    }
+
+    /// Binary PPM (P6), readable by most image viewers
+    pub fn write_ppm(&self, mut writer: impl Write) -> std::io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.to_rgb_bytes())
+    }
+
+    #[cfg(feature = "png")]
+    pub fn write_png(&self, writer: impl Write) -> std::io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut png_writer| png_writer.write_image_data(&self.to_rgb_bytes()))
+            .map_err(std::io::Error::other)
+    }
+
+    /// Save as PNG if `path` ends with `.png`, and as PPM otherwise
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let create = || std::fs::File::create(path).map(std::io::BufWriter::new);
+        if path.ends_with(".png") {
+            #[cfg(feature = "png")]
+            return self.write_png(create()?);
+            #[cfg(not(feature = "png"))]
+            return Err(std::io::Error::other(
+                "Saving PNG images requires the `png` feature",
+            ));
+        }
+        self.write_ppm(create()?)
+    }
+
+    pub fn to_rgb_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|&Rgb(r, g, b)| [r, g, b])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> Grid<bool> {
+        let mut grid = Grid::new(2, 2);
+        grid[(0, 0)] = true;
+        grid[(1, 1)] = true;
+        grid
+    }
+
+    fn palette(&b: &bool) -> Rgb {
+        if b {
+            Rgb::WHITE
+        } else {
+            Rgb::BLACK
+        }
+    }
+
+    #[test]
+    fn test_from_grid() {
+        let image = Image::from_grid(&checkerboard(), 3, palette);
+        assert_eq!((image.width, image.height), (6, 6));
+        assert_eq!(image.get(0, 0), Rgb::WHITE);
+        assert_eq!(image.get(2, 2), Rgb::WHITE);
+        assert_eq!(image.get(3, 2), Rgb::BLACK);
+        assert_eq!(image.get(2, 3), Rgb::BLACK);
+        assert_eq!(image.get(5, 5), Rgb::WHITE);
+    }
+
+    #[test]
+    fn test_overlay_path() {
+        let mut image = Image::from_grid(&checkerboard(), 4, palette);
+        image.overlay_path(&[(0, 0), (1, 0)], Rgb::RED);
+        assert_eq!(image.get(2, 2), Rgb::RED);
+        assert_eq!(image.get(4, 2), Rgb::RED);
+        assert_eq!(image.get(6, 2), Rgb::RED);
+        assert_eq!(image.get(2, 6), Rgb::BLACK);
+        assert_eq!(image.get(0, 0), Rgb::WHITE);
+    }
+
+    #[test]
+    fn test_write_ppm() {
+        let image = Image::from_grid(&checkerboard(), 1, palette);
+        let mut bytes = vec![];
+        image.write_ppm(&mut bytes).unwrap();
+
+        let mut expected = b"P6\n2 2\n255\n".to_vec();
+        expected.extend([255, 255, 255, 0, 0, 0, 0, 0, 0, 255, 255, 255]);
+        assert_eq!(bytes, expected);
+    }
+
+    #[test]
+    fn test_save() {
+        let image = Image::from_grid(&checkerboard(), 1, palette);
+        let path = std::env::temp_dir().join(format!("image_test_{}.ppm", std::process::id()));
+        let path = path.to_str().unwrap();
+        image.save(path).unwrap();
+        let bytes = std::fs::read(path);
+        std::fs::remove_file(path).unwrap();
+        assert!(bytes.unwrap().starts_with(b"P6\n2 2\n255\n"));
+
+        #[cfg(not(feature = "png"))]
+        assert!(image.save("image_test.png").is_err());
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_write_png() {
+        let image = Image::from_grid(&checkerboard(), 2, palette);
+        let mut bytes = vec![];
+        image.write_png(&mut bytes).unwrap();
+
+        let decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+        let mut reader = decoder.read_info().unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        assert_eq!((info.width, info.height), (4, 4));
+        assert_eq!(&buffer[..info.buffer_size()], image.to_rgb_bytes());
+    }
+}
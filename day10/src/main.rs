@@ -1,8 +1,10 @@
 mod grid;
+mod image;
 
 use std::collections::VecDeque;
 
 use crate::grid::Grid;
+use crate::image::{Image, Rgb};
 
 const TEST_INPUT: &str = "-L|F7
 7S-7|
@@ -82,14 +84,13 @@ fn part1(input: &str) -> usize {
     let grid_width = grid.width;
     let grid_height = grid.height;
 
-    let mut distance_grid = Grid::new(grid_width, grid_height);
+    let mut distance_grid = grid.map(|&c| (c == b'S').then_some(0));
 
     let mut start_coord = (grid_width, grid_height);
     for y in 0..grid_height {
         for x in 0..grid_width {
             if grid[(x, y)] == b'S' {
                 start_coord = (x, y);
-                distance_grid[(x, y)] = Some(0);
             }
         }
     }
@@ -102,47 +103,51 @@ fn part1(input: &str) -> usize {
         let (x, y) = current;
 
         let up = (x, y - 1);
-        if y > 0 && distance_grid[up].is_none() {
-            match (grid[(x, y)], grid[up]) {
-                (b'|' | b'L' | b'J' | b'S', b'|' | b'7' | b'F') => {
-                    distance_grid[up] = Some(current_distance + 1);
-                    queue.push_back(up);
-                }
-                _ => {}
-            }
+        if y > 0
+            && distance_grid[up].is_none()
+            && matches!(
+                (grid[(x, y)], grid[up]),
+                (b'|' | b'L' | b'J' | b'S', b'|' | b'7' | b'F')
+            )
+        {
+            distance_grid[up] = Some(current_distance + 1);
+            queue.push_back(up);
         }
 
         let down = (x, y + 1);
-        if y < grid_height - 1 && distance_grid[down].is_none() {
-            match (grid[current], grid[down]) {
-                (b'|' | b'7' | b'F' | b'S', b'|' | b'L' | b'J') => {
-                    distance_grid[down] = Some(current_distance + 1);
-                    queue.push_back(down);
-                }
-                _ => {}
-            }
+        if y < grid_height - 1
+            && distance_grid[down].is_none()
+            && matches!(
+                (grid[current], grid[down]),
+                (b'|' | b'7' | b'F' | b'S', b'|' | b'L' | b'J')
+            )
+        {
+            distance_grid[down] = Some(current_distance + 1);
+            queue.push_back(down);
         }
 
         let left = (x - 1, y);
-        if x > 0 && distance_grid[left].is_none() {
-            match (grid[current], grid[left]) {
-                (b'-' | b'J' | b'7' | b'S', b'-' | b'L' | b'F') => {
-                    distance_grid[left] = Some(current_distance + 1);
-                    queue.push_back(left);
-                }
-                _ => {}
-            }
+        if x > 0
+            && distance_grid[left].is_none()
+            && matches!(
+                (grid[current], grid[left]),
+                (b'-' | b'J' | b'7' | b'S', b'-' | b'L' | b'F')
+            )
+        {
+            distance_grid[left] = Some(current_distance + 1);
+            queue.push_back(left);
         }
 
         let right = (x + 1, y);
-        if x < grid_width - 1 && distance_grid[right].is_none() {
-            match (grid[current], grid[right]) {
-                (b'-' | b'L' | b'F' | b'S', b'-' | b'J' | b'7') => {
-                    distance_grid[right] = Some(current_distance + 1);
-                    queue.push_back(right);
-                }
-                _ => {}
-            }
+        if x < grid_width - 1
+            && distance_grid[right].is_none()
+            && matches!(
+                (grid[current], grid[right]),
+                (b'-' | b'L' | b'F' | b'S', b'-' | b'J' | b'7')
+            )
+        {
+            distance_grid[right] = Some(current_distance + 1);
+            queue.push_back(right);
         }
     }
 
@@ -155,6 +160,7 @@ fn part1(input: &str) -> usize {
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum Status {
     Boundary,
     Interior,
@@ -199,7 +205,8 @@ fn flood_fill(status_grid: &mut Grid<Option<Status>>, start: (isize, isize), rep
     }
 }
 
-fn part2(input: &str) -> isize {
+/// Classify every tile of the (padded) input as loop boundary, interior or exterior
+fn classify_tiles(input: &str) -> Grid<Option<Status>> {
     let mut grid = input
         .lines()
         .map(|line| {
@@ -215,14 +222,13 @@ fn part2(input: &str) -> isize {
 
     let grid = Grid::from_nested(&grid);
 
-    let mut status_grid: Grid<Option<Status>> = Grid::new(grid.width, grid.height);
+    let mut status_grid = grid.map(|&c| (c == b'S').then_some(Status::Boundary));
 
     let mut start_coord = (grid.width, grid.height);
     for y in 0..grid.height {
         for x in 0..grid.width {
             if grid[(x, y)] == b'S' {
                 start_coord = (x, y);
-                status_grid[(x, y)] = Some(Status::Boundary);
             }
         }
     }
@@ -233,47 +239,51 @@ fn part2(input: &str) -> isize {
         let (x, y) = current;
 
         let up = (x, y - 1);
-        if y > 0 && status_grid[up].is_none() {
-            if matches!(
+        if y > 0
+            && status_grid[up].is_none()
+            && matches!(
                 (grid[current], grid[up]),
                 (b'|' | b'L' | b'J' | b'S', b'|' | b'7' | b'F')
-            ) {
-                status_grid[up] = Some(Status::Boundary);
-                queue.push_back(up);
-            }
+            )
+        {
+            status_grid[up] = Some(Status::Boundary);
+            queue.push_back(up);
         }
 
         let down = (x, y + 1);
-        if y < grid.height - 1 && status_grid[down].is_none() {
-            match (grid[current], grid[down]) {
-                (b'|' | b'7' | b'F' | b'S', b'|' | b'L' | b'J') => {
-                    status_grid[down] = Some(Status::Boundary);
-                    queue.push_back(down);
-                }
-                _ => {}
-            }
+        if y < grid.height - 1
+            && status_grid[down].is_none()
+            && matches!(
+                (grid[current], grid[down]),
+                (b'|' | b'7' | b'F' | b'S', b'|' | b'L' | b'J')
+            )
+        {
+            status_grid[down] = Some(Status::Boundary);
+            queue.push_back(down);
         }
 
         let left = (x - 1, y);
-        if x > 0 && status_grid[left].is_none() {
-            match (grid[current], grid[left]) {
-                (b'-' | b'J' | b'7' | b'S', b'-' | b'L' | b'F') => {
-                    status_grid[left] = Some(Status::Boundary);
-                    queue.push_back(left);
-                }
-                _ => {}
-            }
+        if x > 0
+            && status_grid[left].is_none()
+            && matches!(
+                (grid[current], grid[left]),
+                (b'-' | b'J' | b'7' | b'S', b'-' | b'L' | b'F')
+            )
+        {
+            status_grid[left] = Some(Status::Boundary);
+            queue.push_back(left);
         }
 
         let right = (x + 1, y);
-        if x < grid.width - 1 && status_grid[right].is_none() {
-            match (grid[current], grid[right]) {
-                (b'-' | b'L' | b'F' | b'S', b'-' | b'J' | b'7') => {
-                    status_grid[right] = Some(Status::Boundary);
-                    queue.push_back(right);
-                }
-                _ => {}
-            }
+        if x < grid.width - 1
+            && status_grid[right].is_none()
+            && matches!(
+                (grid[current], grid[right]),
+                (b'-' | b'L' | b'F' | b'S', b'-' | b'J' | b'7')
+            )
+        {
+            status_grid[right] = Some(Status::Boundary);
+            queue.push_back(right);
         }
     }
 
@@ -429,6 +439,12 @@ fn part2(input: &str) -> isize {
         }
     }
 
+    status_grid
+}
+
+fn part2(input: &str) -> isize {
+    let status_grid = classify_tiles(input);
+
     let mut result = 0;
     for y in 0..status_grid.height {
         for x in 0..status_grid.width {
@@ -440,7 +456,41 @@ fn part2(input: &str) -> isize {
     result
 }
 
+// Save the loop in white, its interior in green and the exterior in blue
+fn save_status_image(input: &str, path: &str) -> std::io::Result<()> {
+    let image = Image::from_grid(&classify_tiles(input), 8, |status| match status {
+        Some(Status::Boundary) => Rgb::WHITE,
+        Some(Status::Interior) => Rgb::GREEN,
+        Some(Status::Exterior) => Rgb::BLUE,
+        None => Rgb::BLACK,
+    });
+    image.save(path)
+}
+
+/// Path following `flag` in `args`, `None` without the flag. Exits if the path is missing
+fn path_arg<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let position = args.iter().position(|arg| arg == flag)?;
+    match args.get(position + 1) {
+        Some(path) => Some(path),
+        None => {
+            eprintln!("{flag} should be followed by a path");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() {
+    let args: Vec<_> = std::env::args().collect();
+    if let Some(path) = path_arg(&args, "--image") {
+        let input = if args.iter().any(|arg| arg == "--test") {
+            TEST_INPUT
+        } else {
+            INPUT
+        };
+        save_status_image(input, path).unwrap();
+        return;
+    }
+
     assert_eq!(part1(INPUT), 6649);
 
     assert_eq!(part2(TEST_INPUT), 1);
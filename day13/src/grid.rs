@@ -9,7 +9,7 @@ pub struct Grid<T> {
 }
 
 impl<T> Grid<T> {
-    fn is_out_of_bound(self: &Self, (x, y): Point) -> bool {
+    fn is_out_of_bound(&self, (x, y): Point) -> bool {
         x < 0 || x >= self.width || y < 0 || y >= self.height
     }
 
@@ -29,13 +29,103 @@ impl<T> Grid<T> {
         }
     }
 
-    pub fn rows(self: &Self) -> GridRowIter<T> {
+    pub fn rows(&self) -> GridRowIter<'_, T> {
         GridRowIter { grid: self, y: 0 }
     }
 
-    pub fn columns(self: &Self) -> GridColIter<T> {
+    pub fn columns(&self) -> GridColIter<'_, T> {
         GridColIter { grid: self, x: 0 }
     }
+
+    fn point_of_index(&self, index: usize) -> Point {
+        (index as isize % self.width, index as isize / self.width)
+    }
+
+    /// Build a grid of the same size by applying `f` to every cell
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            data: self.data.iter().map(f).collect(),
+        }
+    }
+
+    /// Combine two grids of the same size cell by cell
+    pub fn zip_with<U, V>(&self, other: &Grid<U>, mut f: impl FnMut(&T, &U) -> V) -> Grid<V> {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Grid sizes mismatch"
+        );
+
+        Grid {
+            width: self.width,
+            height: self.height,
+            data: self
+                .data
+                .iter()
+                .zip(other.data.iter())
+                .map(|(lhs, rhs)| f(lhs, rhs))
+                .collect(),
+        }
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Points where the two grids of the same size differ, in row-major order
+    pub fn diff(&self, other: &Self) -> Vec<Point> {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Grid sizes mismatch"
+        );
+
+        self.data
+            .iter()
+            .zip(other.data.iter())
+            .enumerate()
+            .filter(|(_, (lhs, rhs))| lhs != rhs)
+            .map(|(i, _)| self.point_of_index(i))
+            .collect()
+    }
+
+    /// Number of cells where the two grids of the same size differ
+    pub fn hamming_distance(&self, other: &Self) -> usize {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "Grid sizes mismatch"
+        );
+
+        self.data
+            .iter()
+            .zip(other.data.iter())
+            .filter(|(lhs, rhs)| lhs != rhs)
+            .count()
+    }
+
+    /// Number of cells where rows `y1` and `y2` differ
+    pub fn row_hamming_distance(&self, y1: isize, y2: isize) -> usize {
+        let row1 = self.rows().nth(y1 as usize).expect("Row out of bound");
+        let row2 = self.rows().nth(y2 as usize).expect("Row out of bound");
+        row1.iter()
+            .zip(row2)
+            .filter(|(lhs, rhs)| lhs != rhs)
+            .count()
+    }
+
+    /// Number of cells where columns `x1` and `x2` differ
+    pub fn column_hamming_distance(&self, x1: isize, x2: isize) -> usize {
+        let column1 = self
+            .columns()
+            .nth(x1 as usize)
+            .expect("Column out of bound");
+        let column2 = self
+            .columns()
+            .nth(x2 as usize)
+            .expect("Column out of bound");
+        column1.zip(column2).filter(|(lhs, rhs)| lhs != rhs).count()
+    }
 }
 
 impl<T: Clone + Default> Grid<T> {
@@ -131,3 +221,77 @@ impl<'a, T> Iterator for GridColIter<'a, T> {
         self.next()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Grid<u8> {
+        let nested: Vec<_> = input.lines().map(|line| line.as_bytes().to_vec()).collect();
+        Grid::from_nested(&nested)
+    }
+
+    #[test]
+    fn test_map() {
+        let grid = parse("#.\n.#\n..");
+        let mapped = grid.map(|&c| (c == b'#') as u8);
+        assert_eq!((mapped.width, mapped.height), (2, 3));
+        assert_eq!(mapped[(0, 0)], 1);
+        assert_eq!(mapped[(1, 0)], 0);
+        assert_eq!(mapped[(1, 1)], 1);
+        assert_eq!(mapped[(1, 2)], 0);
+    }
+
+    #[test]
+    fn test_zip_with() {
+        let grid = parse("#.\n.#");
+        let layer = grid.map(|&c| if c == b'#' { 10 } else { 1 });
+        let zipped = grid.zip_with(&layer, |&c, &n| (c, n));
+        assert_eq!(zipped[(0, 0)], (b'#', 10));
+        assert_eq!(zipped[(1, 0)], (b'.', 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zip_with_size_mismatch() {
+        parse("#.\n.#").zip_with(&parse("#.."), |_, _| ());
+    }
+
+    #[test]
+    fn test_diff() {
+        let first = parse("#.##..##.\n..#.##.#.\n##......#");
+        let second = parse("..##..##.\n..#.##.#.\n##......#");
+        assert_eq!(first.diff(&first), vec![]);
+        assert_eq!(first.diff(&second), vec![(0, 0)]);
+
+        let third = parse("..##..##.\n..#.##.#.\n##.#....#");
+        assert_eq!(first.diff(&third), vec![(0, 0), (3, 2)]);
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        let first = parse("#...##..#\n#....#..#");
+        let second = parse("#...##..#\n#...##..#");
+        assert_eq!(first.hamming_distance(&first), 0);
+        assert_eq!(first.hamming_distance(&second), 1);
+        assert_eq!(second.hamming_distance(&first), 1);
+    }
+
+    #[test]
+    fn test_row_and_column_hamming_distance() {
+        let grid = parse("#...##..#\n#....#..#\n..##..###");
+        assert_eq!(grid.row_hamming_distance(0, 1), 1);
+        assert_eq!(grid.row_hamming_distance(1, 0), 1);
+        assert_eq!(grid.row_hamming_distance(0, 0), 0);
+        assert_eq!(grid.row_hamming_distance(1, 2), 6);
+        assert_eq!(grid.column_hamming_distance(0, 8), 1);
+        assert_eq!(grid.column_hamming_distance(1, 2), 1);
+        assert_eq!(grid.column_hamming_distance(0, 4), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_row_hamming_distance_out_of_bound() {
+        parse("#.\n.#").row_hamming_distance(0, 2);
+    }
+}
@@ -47,17 +47,6 @@ fn part2(input: &[Grid<u8>]) -> isize {
         .sum()
 }
 
-fn difference_count<T: PartialEq>(
-    first: impl IntoIterator<Item = T>,
-    second: impl IntoIterator<Item = T>,
-) -> isize {
-    first
-        .into_iter()
-        .zip(second)
-        .map(|(e1, e2)| (e1 != e2) as isize)
-        .sum::<isize>()
-}
-
 fn find_line<const IS_VERTICAL: bool>(
     grid: &Grid<u8>,
     max_diffs: isize,
@@ -90,28 +79,22 @@ fn has_reflection<const IS_VERTICAL: bool>(
 
     (0..iterations)
         .scan(0isize, |diffs, i| {
-            let lesser_index = (line_index - i) as usize;
-            let greater_index = (line_index + 1 + i) as usize;
+            let lesser_index = line_index - i;
+            let greater_index = line_index + 1 + i;
 
             *diffs += if IS_VERTICAL {
-                difference_count(
-                    grid.columns().nth(lesser_index).unwrap(),
-                    grid.columns().nth(greater_index).unwrap(),
-                )
+                grid.column_hamming_distance(lesser_index, greater_index)
             } else {
-                difference_count(
-                    grid.rows().nth(lesser_index).unwrap(),
-                    grid.rows().nth(greater_index).unwrap(),
-                )
-            };
+                grid.row_hamming_distance(lesser_index, greater_index)
+            } as isize;
             Some(*diffs)
         })
         .all(|diffs| diffs <= max_diffs)
 }
 
 fn main() {
-    assert_eq!(part1(&parse_input(&TEST_INPUT)), 405);
-    assert_eq!(part1(&parse_input(&INPUT)), 35360);
+    assert_eq!(part1(&parse_input(TEST_INPUT)), 405);
+    assert_eq!(part1(&parse_input(INPUT)), 35360);
 
-    assert_eq!(part2(&parse_input(&INPUT)), 36755);
+    assert_eq!(part2(&parse_input(INPUT)), 36755);
 }
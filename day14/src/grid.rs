@@ -5,6 +5,8 @@ use std::mem::swap;
 type Point = (isize, isize);
 
 #[derive(Eq, PartialEq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "GridRepr<T>"))]
 pub struct Grid<T> {
     pub width: isize,
     pub height: isize,
@@ -12,7 +14,7 @@ pub struct Grid<T> {
 }
 
 impl<T> Grid<T> {
-    fn is_out_of_bound(self: &Self, (x, y): Point) -> bool {
+    fn is_out_of_bound(&self, (x, y): Point) -> bool {
         x < 0 || x >= self.width || y < 0 || y >= self.height
     }
 
@@ -54,11 +56,11 @@ impl<T> Grid<T> {
         }
     }
 
-    pub fn rows(self: &Self) -> GridRowIter<T> {
+    pub fn rows(&self) -> GridRowIter<'_, T> {
         GridRowIter { grid: self, y: 0 }
     }
 
-    pub fn columns(self: &Self) -> GridColIter<T> {
+    pub fn columns(&self) -> GridColIter<'_, T> {
         GridColIter { grid: self, x: 0 }
     }
 }
@@ -90,6 +92,141 @@ impl<T: Clone + Default> Grid<T> {
     }
 }
 
+impl Grid<u8> {
+    /// Encode a character grid as run-length text, one line per row.
+    /// A run is written as `<count><char>`, with the count omitted for single cells.
+    /// e.g. `OO.#..` becomes `2O.#2.`
+    ///
+    /// Digits and `\\` are escaped with a `\\`, and line breaks are written as `\\n` and `\\r`,
+    /// so any grid can be encoded. e.g. `1112..` becomes `3\\1\\22.`
+    pub fn to_rle(&self) -> String {
+        let mut result = String::new();
+        for row in self.rows() {
+            for run in row.chunk_by(|lhs, rhs| lhs == rhs) {
+                if run.len() > 1 {
+                    result += &run.len().to_string();
+                }
+                match run[0] {
+                    b'\n' => result += "\\n",
+                    b'\r' => result += "\\r",
+                    c @ (b'0'..=b'9' | b'\\') => {
+                        result.push('\\');
+                        result.push(c as char);
+                    }
+                    c => result.push(c as char),
+                }
+            }
+            result.push('\n');
+        }
+        result
+    }
+
+    /// Decode a grid written by [`Grid::to_rle`]. Returns `None` on malformed or ragged input,
+    /// including rows longer than the first one or than `MAX_RLE_WIDTH`
+    pub fn from_rle(input: &str) -> Option<Grid<u8>> {
+        const MAX_RLE_WIDTH: usize = 1 << 16;
+
+        let mut rows: Vec<Vec<u8>> = vec![];
+        for line in input.lines() {
+            let max_width = rows.first().map_or(MAX_RLE_WIDTH, |row| row.len());
+            let mut row = vec![];
+            let mut count: Option<usize> = None;
+            let mut chars = line.chars();
+            while let Some(c) = chars.next() {
+                let c = match c {
+                    '0'..='9' => {
+                        let digit = c as usize - '0' as usize;
+                        count = Some(count.unwrap_or(0).checked_mul(10)?.checked_add(digit)?);
+                        if count? > max_width {
+                            return None;
+                        }
+                        continue;
+                    }
+                    '\\' => match chars.next()? {
+                        'n' => b'\n',
+                        'r' => b'\r',
+                        c @ ('0'..='9' | '\\') => c as u8,
+                        _ => return None,
+                    },
+                    // Cells are bytes, which `to_rle` writes as chars up to U+00FF
+                    c => u8::try_from(c).ok()?,
+                };
+                let count = count.take().unwrap_or(1);
+                if row.len() + count > max_width {
+                    return None;
+                }
+                row.extend(std::iter::repeat_n(c, count));
+            }
+            // A trailing count without a character
+            if count.is_some() {
+                return None;
+            }
+            rows.push(row);
+        }
+
+        let width = rows.first()?.len();
+        rows.iter()
+            .all(|row| row.len() == width)
+            .then(|| Grid::from_nested(&rows))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> Grid<T> {
+    /// Binary snapshot of the grid for debugging
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serde::encode_to_vec(self, bincode::config::standard()).unwrap()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::de::DeserializeOwned> Grid<T> {
+    /// Load a snapshot written by [`Grid::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Option<Grid<T>> {
+        bincode::serde::decode_from_slice(bytes, bincode::config::standard())
+            .ok()
+            .map(|(grid, _)| grid)
+    }
+}
+
+// Deserialized grids go through here so that the size always matches the data
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct GridRepr<T> {
+    width: isize,
+    height: isize,
+    data: Box<[T]>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<GridRepr<T>> for Grid<T> {
+    type Error = String;
+
+    fn try_from(
+        GridRepr {
+            width,
+            height,
+            data,
+        }: GridRepr<T>,
+    ) -> Result<Self, Self::Error> {
+        let cell_count = width
+            .checked_mul(height)
+            .and_then(|cell_count| usize::try_from(cell_count).ok());
+        if width < 0 || height < 0 || cell_count != Some(data.len()) {
+            Err(format!(
+                "{width}x{height} grid can't hold {} cells",
+                data.len()
+            ))
+        } else {
+            Ok(Grid {
+                width,
+                height,
+                data,
+            })
+        }
+    }
+}
+
 impl<T> std::ops::Index<Point> for Grid<T> {
     type Output = T;
 
@@ -156,3 +293,79 @@ impl<'a, T> Iterator for GridColIter<'a, T> {
         self.next()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLATFORM: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+";
+
+    fn parse(input: &str) -> Grid<u8> {
+        let nested: Vec<_> = input.lines().map(|line| line.as_bytes().to_vec()).collect();
+        Grid::from_nested(&nested)
+    }
+
+    #[test]
+    fn test_rle() {
+        let grid = parse(PLATFORM);
+        let encoded = grid.to_rle();
+        assert_eq!(encoded, "O4.#4.\nO.2O#4.#\n5.2#3.\n2O.#O4.O\n");
+        assert!(Grid::from_rle(&encoded).unwrap() == grid);
+
+        assert!(Grid::from_rle("12.#\n12.#\n").unwrap() == parse("............#\n............#"));
+        assert!(Grid::from_rle("").is_none());
+        assert!(Grid::from_rle("3.\n2.\n").is_none());
+        assert!(Grid::from_rle("3.\n3\n").is_none());
+    }
+
+    #[test]
+    fn test_rle_escapes() {
+        let grid = parse("1112..\n\\\\..9.");
+        let encoded = grid.to_rle();
+        assert_eq!(encoded, "3\\1\\22.\n2\\\\2.\\9.\n");
+        assert!(Grid::from_rle(&encoded).unwrap() == grid);
+
+        let mut breaks: Grid<u8> = Grid::new(2, 1);
+        breaks[(0, 0)] = b'\n';
+        breaks[(1, 0)] = b'\r';
+        assert_eq!(breaks.to_rle(), "\\n\\r\n");
+        assert!(Grid::from_rle(&breaks.to_rle()).unwrap() == breaks);
+
+        assert!(Grid::from_rle("2\\x\n").is_none());
+        assert!(Grid::from_rle("2.\\\n").is_none());
+    }
+
+    #[test]
+    fn test_rle_rejects_huge_runs() {
+        assert!(Grid::from_rle("99999999999.\n").is_none());
+        assert!(Grid::from_rle("99999999999999999999999.\n").is_none());
+        // Later rows can't be wider than the first one
+        assert!(Grid::from_rle("2.\n99999.\n").is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_binary_snapshot() {
+        let grid = parse(PLATFORM);
+        assert!(Grid::from_bytes(&grid.to_bytes()).unwrap() == grid);
+
+        let mut flags: Grid<bool> = Grid::new(3, 2);
+        flags[(1, 1)] = true;
+        assert!(Grid::<bool>::from_bytes(&flags.to_bytes()).unwrap() == flags);
+
+        let mut bytes = flags.to_bytes();
+        bytes[0] += 2; // corrupt the width
+        assert!(Grid::<bool>::from_bytes(&bytes).is_none());
+
+        let overflowing = GridRepr {
+            width: isize::MAX,
+            height: 3,
+            data: vec![true; 1].into_boxed_slice(),
+        };
+        assert!(Grid::try_from(overflowing).is_err());
+    }
+}
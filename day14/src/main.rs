@@ -1,7 +1,17 @@
 use crate::grid::Grid;
-use std::collections::{hash_map::Entry, HashMap};
+use crate::image::{Image, Rgb};
+use crate::record::Recorder;
+#[cfg(feature = "visualize")]
+use crate::visualize::Visualizer;
+#[cfg(feature = "visualize")]
+use crossterm::style::Stylize;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 mod grid;
+mod image;
+mod record;
+#[cfg(feature = "visualize")]
+mod visualize;
 
 const TEST_INPUT: &str = "O....#....
 O.OO#....#
@@ -29,6 +39,19 @@ fn print_grid(grid: &Grid<u8>) {
     }
 }
 
+fn rock_color(c: &u8) -> Rgb {
+    match c {
+        b'O' => Rgb::YELLOW,
+        b'#' => Rgb::GRAY,
+        _ => Rgb::BLACK,
+    }
+}
+
+// Save rounded rocks in yellow and cube-shaped rocks in gray
+fn save_grid_image(grid: &Grid<u8>, path: &str) -> std::io::Result<()> {
+    Image::from_grid(grid, 8, rock_color).save(path)
+}
+
 fn tilt_west(grid: &mut Grid<u8>) {
     let mut rows: Vec<_> = grid.rows().map(|row| row.to_vec()).collect();
 
@@ -36,12 +59,9 @@ fn tilt_west(grid: &mut Grid<u8>) {
         row.split_mut(|&c| c == b'#').for_each(|free_slice| {
             // partition
             let rounded_rock_count = free_slice.iter().filter(|&&c| c == b'O').count();
-            for x in 0..rounded_rock_count {
-                free_slice[x] = b'O';
-            }
-            for x in rounded_rock_count..free_slice.len() {
-                free_slice[x] = b'.';
-            }
+            let (rounded_rocks, empty_spaces) = free_slice.split_at_mut(rounded_rock_count);
+            rounded_rocks.fill(b'O');
+            empty_spaces.fill(b'.');
         })
     }
 
@@ -67,10 +87,16 @@ fn tilt_south(grid: &mut Grid<u8>) {
 }
 
 fn cycle(grid: &mut Grid<u8>) {
-    tilt_north(grid);
-    tilt_west(grid);
-    tilt_south(grid);
-    tilt_east(grid);
+    cycle_with_hook(grid, &mut |_| {});
+}
+
+/// Spin cycle that calls `on_step` after every tilt
+fn cycle_with_hook(grid: &mut Grid<u8>, on_step: &mut impl FnMut(&Grid<u8>)) {
+    let tilts: [fn(&mut Grid<u8>); 4] = [tilt_north, tilt_west, tilt_south, tilt_east];
+    for tilt in tilts {
+        tilt(grid);
+        on_step(grid);
+    }
 }
 
 fn calculate_load(grid: &Grid<u8>) -> usize {
@@ -110,7 +136,75 @@ fn part2(mut grid: Grid<u8>) -> usize {
     calculate_load(&grid)
 }
 
+// Play spin cycles in the terminal until the platform repeats a previous state
+#[cfg(feature = "visualize")]
+fn visualize(mut grid: Grid<u8>) -> std::io::Result<()> {
+    let mut visualizer = Visualizer::new()?;
+    let mut seen = HashSet::new();
+    let mut result = Ok(());
+    while result.is_ok() && visualizer.is_active() && seen.insert(grid.clone()) {
+        cycle_with_hook(&mut grid, &mut |grid| {
+            if result.is_ok() {
+                result = visualizer.show(grid, |_, &c| match c {
+                    b'O' => 'O'.yellow(),
+                    b'#' => '#'.blue(),
+                    _ => '.'.dark_grey(),
+                });
+            }
+        });
+    }
+    result
+}
+
+// Record every tilt of the spin cycles until the platform repeats a previous state
+fn record(mut grid: Grid<u8>, mut recorder: Recorder) -> std::io::Result<()> {
+    let mut seen = HashSet::new();
+    let mut result = recorder.record(&Image::from_grid(&grid, 4, rock_color));
+    while result.is_ok() && seen.insert(grid.clone()) {
+        cycle_with_hook(&mut grid, &mut |grid| {
+            if result.is_ok() {
+                result = recorder.record(&Image::from_grid(grid, 4, rock_color));
+            }
+        });
+    }
+    result
+}
+
+/// Path following `flag` in `args`, `None` without the flag. Exits if the path is missing
+fn path_arg<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let position = args.iter().position(|arg| arg == flag)?;
+    match args.get(position + 1) {
+        Some(path) => Some(path),
+        None => {
+            eprintln!("{flag} should be followed by a path");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn main() {
+    let args: Vec<_> = std::env::args().collect();
+    let input = if args.iter().any(|arg| arg == "--test") {
+        TEST_INPUT
+    } else {
+        INPUT
+    };
+    if args.iter().any(|arg| arg == "--visualize") {
+        #[cfg(feature = "visualize")]
+        visualize(parse_grid(input)).unwrap();
+        #[cfg(not(feature = "visualize"))]
+        eprintln!("--visualize requires the `visualize` feature");
+        return;
+    }
+    if let Some(path) = path_arg(&args, "--record") {
+        record(parse_grid(input), Recorder::from_path(path).unwrap()).unwrap();
+        return;
+    }
+    if let Some(path) = path_arg(&args, "--image") {
+        save_grid_image(&parse_grid(input), path).unwrap();
+        return;
+    }
+
     let mut grid = parse_grid(TEST_INPUT);
     cycle(&mut grid);
     cycle(&mut grid);
@@ -12,7 +12,7 @@ pub struct Grid<T> {
 }
 
 impl<T> Grid<T> {
-    fn is_out_of_bound(self: &Self, (x, y): Point) -> bool {
+    fn is_out_of_bound(&self, (x, y): Point) -> bool {
         x < 0 || x >= self.width || y < 0 || y >= self.height
     }
 
@@ -54,11 +54,11 @@ impl<T> Grid<T> {
         }
     }
 
-    pub fn rows(self: &Self) -> GridRowIter<T> {
+    pub fn rows(&self) -> GridRowIter<'_, T> {
         GridRowIter { grid: self, y: 0 }
     }
 
-    pub fn columns(self: &Self) -> GridColIter<T> {
+    pub fn columns(&self) -> GridColIter<'_, T> {
         GridColIter { grid: self, x: 0 }
     }
 }
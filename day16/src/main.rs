@@ -1,6 +1,16 @@
 use crate::grid::Grid;
+use crate::image::{Image, Rgb};
+use crate::record::Recorder;
+#[cfg(feature = "visualize")]
+use crate::visualize::Visualizer;
+#[cfg(feature = "visualize")]
+use crossterm::style::Stylize;
 
 mod grid;
+mod image;
+mod record;
+#[cfg(feature = "visualize")]
+mod visualize;
 
 const TEST_INPUT: &str = r#".|...\....
 |.-.\.....
@@ -75,7 +85,16 @@ impl Ray {
     }
 }
 
-fn energize(grid: &Grid<u8>, start: Ray) -> usize {
+fn energized_grid(grid: &Grid<u8>, start: Ray) -> Grid<bool> {
+    energized_grid_with_hook(grid, start, &mut |_, _| {})
+}
+
+/// Ray marching that calls `on_step` with the energized tiles so far every time a ray enters a tile
+fn energized_grid_with_hook(
+    grid: &Grid<u8>,
+    start: Ray,
+    on_step: &mut impl FnMut(&Grid<bool>, Ray),
+) -> Grid<bool> {
     use Direction::*;
 
     // ray marching
@@ -96,6 +115,7 @@ fn energize(grid: &Grid<u8>, start: Ray) -> usize {
         }
 
         energized_grid[ray.origin] = true;
+        on_step(&energized_grid, ray);
 
         match grid[ray.origin] {
             b'|' if matches!(ray.direction, Left | Right) => {
@@ -132,11 +152,29 @@ fn energize(grid: &Grid<u8>, start: Ray) -> usize {
     }
 
     energized_grid
+}
+
+fn energize(grid: &Grid<u8>, start: Ray) -> usize {
+    energized_grid(grid, start)
         .rows()
         .map(|row| row.iter().filter(|&&b| b).count())
         .sum()
 }
 
+fn energized_color(&energized: &bool) -> Rgb {
+    if energized {
+        Rgb::YELLOW
+    } else {
+        Rgb::BLACK
+    }
+}
+
+// Save the tiles energized by a beam from `start` in yellow
+fn save_energized_image(grid: &Grid<u8>, start: Ray, path: &str) -> std::io::Result<()> {
+    let energized = energized_grid(grid, start);
+    Image::from_grid(&energized, 8, energized_color).save(path)
+}
+
 fn parse(input: &str) -> Grid<u8> {
     let nested: Vec<_> = input.lines().map(|line| line.as_bytes().to_vec()).collect();
     Grid::from_nested(&nested)
@@ -153,10 +191,94 @@ fn part2(grid: &Grid<u8>) -> usize {
         .chain((0..grid.height).map(|y| Ray::new((0, y), Left)))
         .chain((0..grid.height).map(|y| Ray::new((grid.width - 1, y), Right)));
 
-    starts.map(|start| energize(&grid, start)).max().unwrap()
+    starts.map(|start| energize(grid, start)).max().unwrap()
+}
+
+// Play the beam entering from the top-left corner in the terminal
+#[cfg(feature = "visualize")]
+fn visualize(grid: &Grid<u8>) -> std::io::Result<()> {
+    let mut visualizer = Visualizer::new()?;
+    let mut result = Ok(());
+    energized_grid_with_hook(grid, Ray::new((0, 0), Right), &mut |energized, ray| {
+        if result.is_ok() {
+            result = visualizer.show(grid, |point, &c| {
+                if point == ray.origin {
+                    let arrow = match ray.direction {
+                        Left => '<',
+                        Right => '>',
+                        Up => '^',
+                        Down => 'v',
+                    };
+                    arrow.red().bold()
+                } else if energized[point] {
+                    (c as char).yellow()
+                } else {
+                    (c as char).dark_grey()
+                }
+            });
+        }
+    });
+    result
+}
+
+// Record the beam entering from the top-left corner. A frame is taken every few tiles it enters so
+// that the real input still fits in about `MAX_FRAMES` frames
+fn record(grid: &Grid<u8>, mut recorder: Recorder) -> std::io::Result<()> {
+    const MAX_FRAMES: usize = 100;
+
+    let start = Ray::new((0, 0), Right);
+    let mut step_count: usize = 0;
+    energized_grid_with_hook(grid, start, &mut |_, _| step_count += 1);
+    let stride = step_count.div_ceil(MAX_FRAMES).max(1);
+
+    let mut step = 0;
+    let mut result = Ok(());
+    energized_grid_with_hook(grid, start, &mut |energized, ray| {
+        step += 1;
+        if result.is_ok() && (step % stride == 0 || step == step_count) {
+            let mut image = Image::from_grid(energized, 4, energized_color);
+            image.overlay_path(&[ray.origin], Rgb::RED);
+            result = recorder.record(&image);
+        }
+    });
+    result
+}
+
+/// Path following `flag` in `args`, `None` without the flag. Exits if the path is missing
+fn path_arg<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let position = args.iter().position(|arg| arg == flag)?;
+    match args.get(position + 1) {
+        Some(path) => Some(path),
+        None => {
+            eprintln!("{flag} should be followed by a path");
+            std::process::exit(1);
+        }
+    }
 }
 
 fn main() {
+    let args: Vec<_> = std::env::args().collect();
+    let input = if args.iter().any(|arg| arg == "--test") {
+        TEST_INPUT
+    } else {
+        INPUT
+    };
+    if args.iter().any(|arg| arg == "--visualize") {
+        #[cfg(feature = "visualize")]
+        visualize(&parse(input)).unwrap();
+        #[cfg(not(feature = "visualize"))]
+        eprintln!("--visualize requires the `visualize` feature");
+        return;
+    }
+    if let Some(path) = path_arg(&args, "--record") {
+        record(&parse(input), Recorder::from_path(path).unwrap()).unwrap();
+        return;
+    }
+    if let Some(path) = path_arg(&args, "--image") {
+        save_energized_image(&parse(input), Ray::new((0, 0), Right), path).unwrap();
+        return;
+    }
+
     let test_grid = parse(TEST_INPUT);
     let grid = parse(INPUT);
 
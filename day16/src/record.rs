@@ -0,0 +1,244 @@
+#![allow(dead_code)]
+
+use crate::image::Image;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+enum Output {
+    /// Numbered PPM files in a directory
+    Frames { directory: PathBuf },
+    #[cfg(feature = "gif")]
+    Gif {
+        path: PathBuf,
+        delay: u16,
+        // Created with the size of the first frame
+        encoder: Option<gif::Encoder<BufWriter<File>>>,
+    },
+}
+
+/// Records simulation frames, one call to `record` per step. All frames should have the same size
+pub struct Recorder {
+    output: Output,
+    frame_count: usize,
+    frame_size: Option<(usize, usize)>,
+}
+
+impl Recorder {
+    /// Write `frame_00000.ppm`, `frame_00001.ppm`, ... into `directory`
+    pub fn frames(directory: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+        Ok(Recorder {
+            output: Output::Frames { directory },
+            frame_count: 0,
+            frame_size: None,
+        })
+    }
+
+    /// Write a looping animated GIF, showing every frame for `delay` hundredths of a second
+    #[cfg(feature = "gif")]
+    pub fn gif(path: impl Into<PathBuf>, delay: u16) -> Self {
+        Recorder {
+            output: Output::Gif {
+                path: path.into(),
+                delay,
+                encoder: None,
+            },
+            frame_count: 0,
+            frame_size: None,
+        }
+    }
+
+    /// Pick the output from the path: a `.gif` file or a directory of frames
+    pub fn from_path(path: &str) -> std::io::Result<Self> {
+        if path.ends_with(".gif") {
+            #[cfg(feature = "gif")]
+            return Ok(Recorder::gif(path, 5));
+            #[cfg(not(feature = "gif"))]
+            return Err(std::io::Error::other(
+                "GIF recording requires the `gif` feature",
+            ));
+        }
+        Recorder::frames(path)
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    pub fn record(&mut self, image: &Image) -> std::io::Result<()> {
+        let size = (image.width, image.height);
+        let first_size = *self.frame_size.get_or_insert(size);
+        if size != first_size {
+            return Err(std::io::Error::other(format!(
+                "{}x{} frame doesn't match the {}x{} first frame",
+                size.0, size.1, first_size.0, first_size.1
+            )));
+        }
+
+        match &mut self.output {
+            Output::Frames { directory } => {
+                let path = directory.join(format!("frame_{:05}.ppm", self.frame_count));
+                image.write_ppm(BufWriter::new(File::create(path)?))?;
+            }
+            #[cfg(feature = "gif")]
+            Output::Gif {
+                path,
+                delay,
+                encoder,
+            } => {
+                let encoder = match encoder {
+                    Some(encoder) => encoder,
+                    None => {
+                        let (Ok(width), Ok(height)) =
+                            (u16::try_from(image.width), u16::try_from(image.height))
+                        else {
+                            return Err(std::io::Error::other(format!(
+                                "{}x{} frame is too large for a GIF",
+                                image.width, image.height
+                            )));
+                        };
+                        let mut new_encoder = gif::Encoder::new(
+                            BufWriter::new(File::create(path)?),
+                            width,
+                            height,
+                            &[],
+                        )
+                        .map_err(std::io::Error::other)?;
+                        new_encoder
+                            .set_repeat(gif::Repeat::Infinite)
+                            .map_err(std::io::Error::other)?;
+                        encoder.insert(new_encoder)
+                    }
+                };
+
+                let mut frame = gif_frame(image);
+                frame.delay = *delay;
+                encoder.write_frame(&frame).map_err(std::io::Error::other)?;
+            }
+        }
+        self.frame_count += 1;
+        Ok(())
+    }
+}
+
+// Grid renders only use a handful of colors, so index them exactly and only fall back to
+// quantization when there are more than a GIF palette can hold. The size should already be checked
+// to fit in `u16`
+#[cfg(feature = "gif")]
+fn gif_frame(image: &Image) -> gif::Frame<'static> {
+    let (width, height) = (image.width as u16, image.height as u16);
+
+    let mut palette: Vec<crate::image::Rgb> = vec![];
+    let mut indices = Vec::with_capacity(image.width * image.height);
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let color = image.get(x, y);
+            let index = match palette.iter().position(|&c| c == color) {
+                Some(index) => index,
+                None if palette.len() < 256 => {
+                    palette.push(color);
+                    palette.len() - 1
+                }
+                None => return gif::Frame::from_rgb(width, height, &image.to_rgb_bytes()),
+            };
+            indices.push(index as u8);
+        }
+    }
+
+    let palette: Vec<u8> = palette
+        .iter()
+        .flat_map(|&crate::image::Rgb(r, g, b)| [r, g, b])
+        .collect();
+    gif::Frame::from_palette_pixels(width, height, indices, palette, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+    use crate::image::Rgb;
+
+    fn frames() -> Vec<Image> {
+        (0..3)
+            .map(|i| {
+                let mut grid: Grid<bool> = Grid::new(3, 2);
+                grid[(i, 1)] = true;
+                Image::from_grid(&grid, 2, |&b| if b { Rgb::YELLOW } else { Rgb::BLACK })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_record_frames() {
+        let directory = std::env::temp_dir().join(format!("day16_frames_{}", std::process::id()));
+        let mut recorder = Recorder::from_path(directory.to_str().unwrap()).unwrap();
+        for frame in frames() {
+            recorder.record(&frame).unwrap();
+        }
+        assert_eq!(recorder.frame_count(), 3);
+
+        let mut expected = vec![];
+        frames()[2].write_ppm(&mut expected).unwrap();
+        assert_eq!(
+            std::fs::read(directory.join("frame_00002.ppm")).unwrap(),
+            expected
+        );
+        assert!(!directory.join("frame_00003.ppm").exists());
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_record_size_mismatch() {
+        let directory = std::env::temp_dir().join(format!("day16_resized_{}", std::process::id()));
+        let mut recorder = Recorder::frames(&directory).unwrap();
+        recorder.record(&frames()[0]).unwrap();
+
+        let smaller = Image::from_grid(&Grid::<bool>::new(2, 2), 2, |_| Rgb::BLACK);
+        assert!(recorder.record(&smaller).is_err());
+        assert_eq!(recorder.frame_count(), 1);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[cfg(feature = "gif")]
+    #[test]
+    fn test_record_gif() {
+        let path = std::env::temp_dir().join(format!("day16_{}.gif", std::process::id()));
+        let mut recorder = Recorder::gif(&path, 10);
+        for frame in frames() {
+            recorder.record(&frame).unwrap();
+        }
+        drop(recorder);
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(File::open(&path).unwrap()).unwrap();
+        let mut frame_count = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            let rgb: Vec<u8> = frame
+                .buffer
+                .chunks(4)
+                .flat_map(|rgba| rgba[..3].to_vec())
+                .collect();
+            assert_eq!(rgb, frames()[frame_count].to_rgb_bytes());
+            assert_eq!(frame.delay, 10);
+            frame_count += 1;
+        }
+        assert_eq!(frame_count, 3);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "gif")]
+    #[test]
+    fn test_record_gif_too_large() {
+        let path = std::env::temp_dir().join(format!("day16_large_{}.gif", std::process::id()));
+        let mut recorder = Recorder::gif(&path, 10);
+        let wide = Image::from_grid(&Grid::<bool>::new(70_000, 1), 1, |_| Rgb::BLACK);
+        assert!(recorder.record(&wide).is_err());
+        assert!(!path.exists());
+    }
+}
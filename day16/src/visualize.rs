@@ -0,0 +1,136 @@
+#![allow(dead_code)]
+
+use crate::grid::Grid;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::StyledContent;
+use crossterm::{cursor, execute, queue, terminal};
+use std::io::{stdout, Write};
+use std::time::{Duration, Instant};
+
+type Point = (isize, isize);
+
+const MIN_DELAY: Duration = Duration::from_millis(1);
+const MAX_DELAY: Duration = Duration::from_secs(2);
+
+/// Terminal playback of a simulation, one frame per call to `show`.
+///
+/// Controls: `space` pause/resume, `n` single step while paused, `+`/`-` speed, `q` quit
+pub struct Visualizer {
+    delay: Duration,
+    paused: bool,
+    active: bool,
+    step: usize,
+}
+
+impl Visualizer {
+    pub fn new() -> std::io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        // Created before touching the screen so that dropping it on error restores the terminal
+        let visualizer = Visualizer {
+            delay: Duration::from_millis(100),
+            paused: false,
+            active: true,
+            step: 0,
+        };
+        execute!(stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(visualizer)
+    }
+
+    /// Whether the user has not quit yet. Simulations driving the visualizer can stop early
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Draw a frame and wait for the current delay, or until the user steps while paused
+    pub fn show<T>(
+        &mut self,
+        grid: &Grid<T>,
+        palette: impl Fn(Point, &T) -> StyledContent<char>,
+    ) -> std::io::Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+
+        let mut frame = String::new();
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                frame += &palette((x, y), &grid[(x, y)]).to_string();
+            }
+            frame += "\r\n";
+        }
+
+        let mut stdout = stdout();
+        queue!(stdout, cursor::MoveTo(0, 0))?;
+        stdout.write_all(frame.as_bytes())?;
+        self.step += 1;
+        self.draw_status()?;
+
+        self.wait()
+    }
+
+    fn draw_status(&self) -> std::io::Result<()> {
+        let mut stdout = stdout();
+        queue!(
+            stdout,
+            terminal::Clear(terminal::ClearType::CurrentLine),
+            cursor::MoveToColumn(0)
+        )?;
+        write!(
+            stdout,
+            "step {} | {}ms{} | space: pause, n: step, +/-: speed, q: quit",
+            self.step,
+            self.delay.as_millis(),
+            if self.paused { " (paused)" } else { "" }
+        )?;
+        stdout.flush()
+    }
+
+    fn wait(&mut self) -> std::io::Result<()> {
+        let deadline = Instant::now() + self.delay;
+        loop {
+            let event = if self.paused {
+                event::read()?
+            } else {
+                let now = Instant::now();
+                if now >= deadline || !event::poll(deadline - now)? {
+                    return Ok(());
+                }
+                event::read()?
+            };
+
+            let Event::Key(key) = event else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char(' ') => self.paused = !self.paused,
+                KeyCode::Char('n') | KeyCode::Right if self.paused => return Ok(()),
+                KeyCode::Char('+') | KeyCode::Char('=') => {
+                    self.delay = (self.delay / 2).max(MIN_DELAY)
+                }
+                KeyCode::Char('-') => self.delay = (self.delay * 2).min(MAX_DELAY),
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    self.active = false;
+                    return Ok(());
+                }
+                // Raw mode swallows the interrupt signal
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.active = false;
+                    return Ok(());
+                }
+                _ => {}
+            }
+            self.draw_status()?;
+        }
+    }
+}
+
+impl Drop for Visualizer {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
@@ -3,6 +3,7 @@ use crate::vec2::Vec2;
 use Direction::*;
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Direction {
     Up,
     Down,
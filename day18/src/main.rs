@@ -3,7 +3,6 @@ mod vec2;
 
 use crate::direction::Direction;
 use crate::vec2::Vec2;
-use std::isize;
 
 const TEST_INPUT: &str = "R 6 (#70c710)
 D 5 (#0dc571)
@@ -1,6 +1,7 @@
 use std::ops::{Add, Mul, Sub};
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec2 {
     pub x: isize,
     pub y: isize,
@@ -1,22 +1,73 @@
-const INPUT: &'static str = include_str!("./input.txt");
+use std::collections::BTreeMap;
 
+const TEST_INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+const INPUT: &str = include_str!("./input.txt");
+
+/// Colors that the power of a set is taken over
+const POWER_COLORS: [&str; 3] = ["red", "green", "blue"];
+
+/// Multiset of cubes keyed by color name
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 struct Set {
-    r: i32,
-    g: i32,
-    b: i32,
+    counts: BTreeMap<String, i32>,
 }
 
 impl Set {
     fn new() -> Self {
-        Set { r: 0, g: 0, b: 0 }
+        Set::default()
+    }
+
+    /// Parse a description such as `3 blue, 4 red`
+    fn parse(input: &str) -> Option<Self> {
+        let mut set = Set::new();
+        for single_color_cubes in input.split(',').map(str::trim) {
+            let (count, color) = single_color_cubes.split_once(' ')?;
+            let count: i32 = count.parse().ok()?;
+            *set.counts.entry(color.trim().to_owned()).or_default() += count;
+        }
+        Some(set)
+    }
+
+    fn count(&self, color: &str) -> i32 {
+        self.counts.get(color).copied().unwrap_or(0)
     }
 
     fn max(&self, other: &Self) -> Self {
-        Set {
-            r: self.r.max(other.r),
-            g: self.g.max(other.g),
-            b: self.b.max(other.b),
+        let mut result = self.clone();
+        for (color, &count) in &other.counts {
+            let entry = result.counts.entry(color.clone()).or_default();
+            *entry = (*entry).max(count);
         }
+        result
+    }
+
+    /// Whether every cube of this set could have been drawn from `bag`
+    fn is_subset(&self, bag: &Self) -> bool {
+        self.counts
+            .iter()
+            .all(|(color, &count)| count <= bag.count(color))
+    }
+
+    /// Product of the counts of `colors`. A color that isn't in the set makes the power 0
+    fn power(&self, colors: &[&str]) -> i32 {
+        colors.iter().map(|color| self.count(color)).product()
+    }
+}
+
+impl std::fmt::Display for Set {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (color, count)) in self.counts.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{count} {color}")?;
+        }
+        Ok(())
     }
 }
 
@@ -25,61 +76,196 @@ struct Game {
     sets: Vec<Set>,
 }
 
-fn parse(input: &str) -> Option<Vec<Game>> {
-    input
+impl Game {
+    /// Fewest cubes of each color that make this game possible
+    fn minimal_bag(&self) -> Set {
+        self.sets.iter().fold(Set::new(), |set, acc| set.max(acc))
+    }
+
+    fn is_possible_with(&self, bag: &Set) -> bool {
+        self.sets.iter().all(|set| set.is_subset(bag))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct ColorStats {
+    max: i32,
+    mean: f64,
+}
+
+struct Games {
+    games: Vec<Game>,
+}
+
+impl Games {
+    fn minimal_bags(&self) -> impl Iterator<Item = (i32, Set)> + '_ {
+        self.games.iter().map(|game| (game.id, game.minimal_bag()))
+    }
+
+    /// Ids of the games that are possible with `bag`
+    fn possible_with(&self, bag: &Set) -> Vec<i32> {
+        self.games
+            .iter()
+            .filter(|game| game.is_possible_with(bag))
+            .map(|game| game.id)
+            .collect()
+    }
+
+    /// Max and mean of the minimal bag count of each color across games.
+    /// Games that never show a color count as 0 for it
+    fn color_stats(&self) -> BTreeMap<String, ColorStats> {
+        let minimal_bags: Vec<_> = self.minimal_bags().map(|(_, bag)| bag).collect();
+        let colors = minimal_bags
+            .iter()
+            .fold(Set::new(), |set, acc| set.max(acc));
+
+        colors
+            .counts
+            .into_iter()
+            .map(|(color, max)| {
+                let sum: i32 = minimal_bags.iter().map(|bag| bag.count(&color)).sum();
+                let mean = sum as f64 / minimal_bags.len() as f64;
+                (color, ColorStats { max, mean })
+            })
+            .collect()
+    }
+
+    /// Largest bag holding the same number of cubes of every color with which at most `k` games
+    /// are possible, or `None` if no bag is small enough or every bag is
+    fn largest_uniform_bag(&self, k: usize) -> Option<Set> {
+        // A game is possible with a uniform bag iff its most frequent color fits
+        let mut largest_counts: Vec<i32> = self
+            .minimal_bags()
+            .map(|(_, bag)| bag.counts.values().copied().max().unwrap_or(0))
+            .collect();
+        largest_counts.sort_unstable();
+
+        let cubes_per_color = largest_counts.get(k)? - 1;
+        if cubes_per_color < 0 {
+            return None;
+        }
+
+        let colors = self
+            .minimal_bags()
+            .fold(Set::new(), |set, (_, acc)| set.max(&acc));
+        Some(Set {
+            counts: colors
+                .counts
+                .into_keys()
+                .map(|color| (color, cubes_per_color))
+                .collect(),
+        })
+    }
+}
+
+fn parse(input: &str) -> Option<Games> {
+    let games = input
         .lines()
         .map(|line| {
-            let (id, games) = line.strip_prefix("Game ")?.split_once(":")?;
+            let (id, games) = line.strip_prefix("Game ")?.split_once(':')?;
             let id = id.parse().ok()?;
-            let sets: Vec<_> = games
-                .split("; ")
-                .map(str::trim)
-                .map(|set_str| {
-                    let mut set = Set::new();
-                    for single_color_cubes in set_str.split(", ") {
-                        let (count, color) = single_color_cubes.split_once(" ").unwrap();
-                        let count = count.parse().ok().unwrap();
-                        match color {
-                            "red" => set.r = count,
-                            "green" => set.g = count,
-                            "blue" => set.b = count,
-                            _ => {}
-                        }
-                    }
-                    set
-                })
-                .collect();
+            let sets = games
+                .split(';')
+                .map(Set::parse)
+                .collect::<Option<Vec<_>>>()?;
 
             Some(Game { id, sets })
         })
-        .collect()
+        .collect::<Option<Vec<_>>>()?;
+    Some(Games { games })
 }
 
-fn part1(games: &[Game]) -> i32 {
-    let has_enough_balls = |game: &&Game| {
-        game.sets
-            .iter()
-            .all(|set| set.r <= 12 && set.g <= 13 && set.b <= 14)
-    };
+fn part1(games: &Games, bag: &Set) -> i32 {
+    games.possible_with(bag).iter().sum()
+}
 
+fn part2(games: &Games) -> i32 {
     games
-        .iter()
-        .filter(has_enough_balls)
-        .map(|game| game.id)
+        .minimal_bags()
+        .map(|(_, bag)| bag.power(&POWER_COLORS))
         .sum()
 }
 
-fn part2(games: &[Game]) -> i32 {
-    games.iter().fold(0, |result, game| {
-        let max_set = game.sets.iter().fold(Set::new(), |set, acc| set.max(acc));
-        result + max_set.r * max_set.g * max_set.b
-    })
+fn report(games: &Games, bag: &Set) {
+    let possible = games.possible_with(bag);
+    println!("Bag: {bag}");
+    println!(
+        "{} of {} games possible, id sum {}",
+        possible.len(),
+        games.games.len(),
+        possible.iter().sum::<i32>()
+    );
+    println!("Possible games: {possible:?}");
+    for (color, stats) in games.color_stats() {
+        println!(
+            "{color}: max {}, mean {:.2} (bag has {})",
+            stats.max,
+            stats.mean,
+            bag.count(&color)
+        );
+    }
 }
 
 fn main() {
+    let args: Vec<_> = std::env::args().collect();
+    if let Some(bag) = args.iter().skip_while(|&arg| arg != "--bag").nth(1) {
+        let bag = Set::parse(bag).expect("Bag should look like \"12 red, 13 green\"");
+        report(&parse(INPUT).unwrap(), &bag);
+        return;
+    }
+    if let Some(k) = args.iter().skip_while(|&arg| arg != "--keep").nth(1) {
+        let k: usize = k.parse().expect("K should be a number");
+        match parse(INPUT).unwrap().largest_uniform_bag(k) {
+            Some(bag) => println!("Largest uniform bag keeping at most {k} games possible: {bag}"),
+            None => println!("No uniform bag keeps at most {k} games possible"),
+        }
+        return;
+    }
+
+    let bag = Set::parse("12 red, 13 green, 14 blue").unwrap();
+
+    let test_data = parse(TEST_INPUT).unwrap();
+    assert_eq!(part1(&test_data, &bag), 8);
+    assert_eq!(part2(&test_data), 2286);
+
+    assert_eq!(test_data.possible_with(&bag), vec![1, 2, 5]);
+    let minimal_bags: Vec<_> = test_data.minimal_bags().map(|(_, bag)| bag).collect();
+    assert_eq!(
+        minimal_bags[0],
+        Set::parse("4 red, 2 green, 6 blue").unwrap()
+    );
+    let stats = test_data.color_stats();
+    assert_eq!(stats["red"], ColorStats { max: 20, mean: 9.0 });
+    assert_eq!(stats["blue"], ColorStats { max: 15, mean: 6.6 });
+    assert_eq!(
+        test_data.largest_uniform_bag(0),
+        Set::parse("3 blue, 3 green, 3 red")
+    );
+    assert_eq!(
+        test_data.largest_uniform_bag(2),
+        Set::parse("5 blue, 5 green, 5 red")
+    );
+    assert_eq!(test_data.largest_uniform_bag(5), None);
+
+    // Colors outside of the bag make a game impossible
+    let extra_colors = parse("Game 1: 1 red, 2 yellow; 3 blue\nGame 2: 2 red; 1 green").unwrap();
+    assert_eq!(extra_colors.games[0].sets[0].count("yellow"), 2);
+    assert_eq!(part1(&extra_colors, &bag), 2);
+    let rainbow_bag = Set::parse("12 red, 13 green, 14 blue, 2 yellow").unwrap();
+    assert_eq!(part1(&extra_colors, &rainbow_bag), 3);
+    // Games missing a color have no power, and other colors don't count toward it
+    let missing_blue =
+        parse("Game 1: 1 red, 2 yellow; 3 blue, 2 green\nGame 2: 2 red; 1 green").unwrap();
+    assert_eq!(part2(&missing_blue), 6);
+    assert_eq!(minimal_bags[0].power(&POWER_COLORS), 48);
+    assert_eq!(
+        Set::parse("4 red, 2 green").unwrap().power(&POWER_COLORS),
+        0
+    );
+
     let data = parse(INPUT).unwrap();
 
-    let result1 = part1(&data);
+    let result1 = part1(&data, &bag);
     let result2 = part2(&data);
     assert_eq!(result1, 3059);
     assert_eq!(result2, 65371);
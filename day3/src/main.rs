@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::ops::Range;
 
 const TEST_INPUT: &str = "467..114..
 ...*......
@@ -14,133 +15,280 @@ const TEST_INPUT: &str = "467..114..
 
 const INPUT: &str = include_str!("./input.txt");
 
-fn part1(input: &str) -> i32 {
-    let lines: Vec<_> = input.lines().map(|line| line.as_bytes()).collect();
-    let mut sum: i32 = 0;
-    for (y, line) in lines.iter().enumerate() {
-        let mut start = 0; // column number
-        while start < line.len() {
-            if line[start].is_ascii_digit() {
-                let mut end = start + 1;
-                while end < line.len() && line[end].is_ascii_digit() {
-                    end += 1
-                }
-                let num: i32 = std::str::from_utf8(&line[start..end])
-                    .unwrap()
-                    .parse()
-                    .unwrap();
-
-                let mut is_part_number = false;
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct PartNumber {
+    value: i32,
+    row: usize,
+    columns: Range<usize>,
+}
 
-                if start > 0 && line[start - 1] != b'.' {
-                    is_part_number = true;
-                }
-                if end != line.len() && line[end] != b'.' {
-                    is_part_number = true;
-                }
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Symbol {
+    symbol: u8,
+    row: usize,
+    column: usize,
+}
 
-                let before_start = start.saturating_sub(1);
-                let after_end = (end + 1).min(line.len());
+/// Every number and symbol of the engine schematic, with which numbers touch which symbols
+struct Schematic {
+    numbers: Vec<PartNumber>,
+    symbols: Vec<Symbol>,
+    /// Indices into `numbers` adjacent to each symbol (including diagonally)
+    adjacency: Vec<Vec<usize>>,
+}
 
-                if y > 0 {
-                    let last_line = lines[y - 1];
-                    for x in before_start..after_end {
-                        if last_line[x] != b'.' {
-                            is_part_number = true;
-                        }
+impl Schematic {
+    fn parse(input: &str) -> Self {
+        let mut numbers = vec![];
+        let mut symbols = vec![];
+        for (row, line) in input.lines().enumerate() {
+            let line = line.as_bytes();
+            let mut start = 0; // column number
+            while start < line.len() {
+                if line[start].is_ascii_digit() {
+                    let mut end = start + 1;
+                    while end < line.len() && line[end].is_ascii_digit() {
+                        end += 1
                     }
-                }
-                if y + 1 < line.len() {
-                    let next_line = lines[y + 1];
-                    for x in before_start..after_end {
-                        if next_line[x] != b'.' {
-                            is_part_number = true;
-                        }
+                    let value: i32 = std::str::from_utf8(&line[start..end])
+                        .unwrap()
+                        .parse()
+                        .unwrap();
+                    numbers.push(PartNumber {
+                        value,
+                        row,
+                        columns: start..end,
+                    });
+                    start = end;
+                } else {
+                    // Short lines are padded with `.`, and stray whitespace is not a symbol
+                    if line[start] != b'.' && !line[start].is_ascii_whitespace() {
+                        symbols.push(Symbol {
+                            symbol: line[start],
+                            row,
+                            column: start,
+                        });
                     }
+                    start += 1;
                 }
+            }
+        }
 
-                if is_part_number {
-                    sum += num;
+        // Neighbours are looked up by position rather than by indexing rows, so schematics of any
+        // shape (including ragged lines) need no bounds checks
+        let symbol_at: HashMap<Coord, usize> = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, symbol)| ((symbol.column, symbol.row), i))
+            .collect();
+        let mut adjacency = vec![vec![]; symbols.len()];
+        for (number_index, number) in numbers.iter().enumerate() {
+            for y in number.row.saturating_sub(1)..=number.row + 1 {
+                for x in number.columns.start.saturating_sub(1)..=number.columns.end {
+                    if let Some(&symbol_index) = symbol_at.get(&(x, y)) {
+                        adjacency[symbol_index].push(number_index);
+                    }
                 }
-
-                start = end;
-            } else {
-                start += 1;
             }
         }
+
+        Schematic {
+            numbers,
+            symbols,
+            adjacency,
+        }
+    }
+
+    /// Numbers adjacent to the symbol at `symbol_index`
+    fn neighbours(&self, symbol_index: usize) -> impl Iterator<Item = &PartNumber> {
+        self.adjacency[symbol_index]
+            .iter()
+            .map(|&number_index| &self.numbers[number_index])
+    }
+
+    /// Numbers adjacent to at least one symbol
+    fn part_numbers(&self) -> impl Iterator<Item = &PartNumber> {
+        let mut is_part_number = vec![false; self.numbers.len()];
+        for &number_index in self.adjacency.iter().flatten() {
+            is_part_number[number_index] = true;
+        }
+        self.numbers
+            .iter()
+            .zip(is_part_number)
+            .filter_map(|(number, is_part_number)| is_part_number.then_some(number))
     }
-    sum
 }
 
 type Coord = (usize, usize);
 
-fn add_gear_number(gears: &mut HashMap<Coord, Vec<i32>>, coord: Coord, num: i32) {
-    gears.entry(coord).or_insert_with(Vec::new).push(num);
+fn part1(schematic: &Schematic) -> i32 {
+    schematic.part_numbers().map(|number| number.value).sum()
 }
 
-fn part2(input: &str) -> i32 {
-    let mut gears = HashMap::new();
-    let lines: Vec<_> = input.lines().map(|line| line.as_bytes()).collect();
-    for (y, line) in lines.iter().enumerate() {
-        let mut start = 0; // column number
-        while start < line.len() {
-            if line[start].is_ascii_digit() {
-                let mut end = start + 1;
-                while end < line.len() && line[end].is_ascii_digit() {
-                    end += 1
-                }
-                let num: i32 = std::str::from_utf8(&line[start..end])
-                    .unwrap()
-                    .parse()
-                    .unwrap();
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Combine {
+    Product,
+    Sum,
+    Max,
+}
 
-                let before_start = start.saturating_sub(1);
+/// Which symbols count as gears and how their adjacent numbers make a gear ratio
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct GearRule {
+    symbol: u8,
+    /// A symbol is only a gear when exactly this many numbers are adjacent to it
+    neighbour_count: usize,
+    combine: Combine,
+}
 
-                if line[before_start] == b'*' {
-                    add_gear_number(&mut gears, (before_start, y), num);
-                }
-                if end != line.len() && line[end] == b'*' {
-                    add_gear_number(&mut gears, (end, y), num);
-                }
+impl GearRule {
+    /// `*` with exactly two numbers, multiplied
+    const STANDARD: GearRule = GearRule {
+        symbol: b'*',
+        neighbour_count: 2,
+        combine: Combine::Product,
+    };
 
-                let after_end = (end + 1).min(line.len());
-                if y > 0 {
-                    let last_line = lines[y - 1];
-                    for x in before_start..after_end {
-                        if last_line[x] == b'*' {
-                            add_gear_number(&mut gears, (x, y - 1), num);
-                        }
-                    }
-                }
-                if y + 1 < line.len() {
-                    let next_line = lines[y + 1];
+    /// Parse a rule like `# 3 sum`: the symbol, the neighbour count and `product`, `sum` or `max`
+    fn parse(s: &str) -> Option<GearRule> {
+        let mut parts = s.split_whitespace();
+        let symbol = match parts.next()?.as_bytes() {
+            &[symbol] if symbol != b'.' && !symbol.is_ascii_digit() => symbol,
+            _ => return None,
+        };
+        let neighbour_count = parts.next()?.parse().ok()?;
+        let combine = match parts.next()? {
+            "product" => Combine::Product,
+            "sum" => Combine::Sum,
+            "max" => Combine::Max,
+            _ => return None,
+        };
+        parts.next().is_none().then_some(GearRule {
+            symbol,
+            neighbour_count,
+            combine,
+        })
+    }
+}
 
-                    for x in before_start..after_end {
-                        if next_line[x] == b'*' {
-                            add_gear_number(&mut gears, (x, y + 1), num);
-                        }
-                    }
+impl Schematic {
+    /// Ratios of every symbol that is a gear under `rule`
+    fn gear_ratios(&self, rule: &GearRule) -> impl Iterator<Item = i64> + '_ {
+        let rule = *rule;
+        self.symbols
+            .iter()
+            .enumerate()
+            .filter(move |(i, symbol)| {
+                symbol.symbol == rule.symbol && self.adjacency[*i].len() == rule.neighbour_count
+            })
+            .map(move |(i, _)| {
+                let values = self.neighbours(i).map(|number| number.value as i64);
+                match rule.combine {
+                    Combine::Product => values.product(),
+                    Combine::Sum => values.sum(),
+                    Combine::Max => values.max().unwrap_or(0),
                 }
-                start = end;
-            } else {
-                start += 1;
-            }
-        }
+            })
     }
+}
+
+fn part2(schematic: &Schematic, rule: &GearRule) -> i64 {
+    schematic.gear_ratios(rule).sum()
+}
 
-    let mut sum = 0;
-    for numbers in gears.values() {
-        if numbers.len() == 2 {
-            sum += numbers[0] * numbers[1];
+fn main() {
+    let test_schematic = Schematic::parse(TEST_INPUT);
+    let schematic = Schematic::parse(INPUT);
+
+    assert_eq!(test_schematic.numbers.len(), 10);
+    assert_eq!(
+        test_schematic.numbers[1],
+        PartNumber {
+            value: 114,
+            row: 0,
+            columns: 5..8
         }
-    }
+    );
+    assert_eq!(test_schematic.symbols.len(), 6);
+    assert_eq!(
+        test_schematic
+            .neighbours(0)
+            .map(|n| n.value)
+            .collect::<Vec<_>>(),
+        vec![467, 35]
+    );
 
-    sum
+    assert_eq!(part1(&test_schematic), 4361);
+    assert_eq!(part1(&schematic), 527364);
+    assert_eq!(part2(&test_schematic, &GearRule::STANDARD), 467835);
+    assert_eq!(part2(&schematic, &GearRule::STANDARD), 79026871);
+
+    let sum_of_pairs = GearRule {
+        combine: Combine::Sum,
+        ..GearRule::STANDARD
+    };
+    assert_eq!(part2(&test_schematic, &sum_of_pairs), 467 + 35 + 755 + 598);
+    let largest_of_pairs = GearRule {
+        combine: Combine::Max,
+        ..GearRule::STANDARD
+    };
+    assert_eq!(part2(&test_schematic, &largest_of_pairs), 467 + 755);
+    let lone_hashes = GearRule {
+        symbol: b'#',
+        neighbour_count: 1,
+        combine: Combine::Sum,
+    };
+    assert_eq!(part2(&test_schematic, &lone_hashes), 633);
+    assert_eq!(GearRule::parse("# 1 sum"), Some(lone_hashes));
+    assert_eq!(GearRule::parse("* 2 product"), Some(GearRule::STANDARD));
+    assert_eq!(GearRule::parse("** 2 product"), None);
+    assert_eq!(GearRule::parse("* 2"), None);
+
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(rule) = args.iter().skip_while(|&arg| arg != "--gear").nth(1) {
+        let rule = GearRule::parse(rule).expect("Gear rule should look like `* 2 product`");
+        println!("{}", part2(&schematic, &rule));
+    }
 }
 
-fn main() {
-    assert_eq!(part1(TEST_INPUT), 4361);
-    assert_eq!(part1(INPUT), 527364);
-    assert_eq!(part2(TEST_INPUT), 467835);
-    assert_eq!(part2(INPUT), 79026871);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values<'a>(numbers: impl Iterator<Item = &'a PartNumber>) -> Vec<i32> {
+        numbers.map(|number| number.value).collect()
+    }
+
+    #[test]
+    fn test_tall_schematic() {
+        let schematic = Schematic::parse("1.\n..\n..\n..\n.2\n*.\n3.\n");
+        assert_eq!(values(schematic.part_numbers()), vec![2, 3]);
+        assert_eq!(part2(&schematic, &GearRule::STANDARD), 6);
+    }
+
+    #[test]
+    fn test_wide_schematic() {
+        let schematic = Schematic::parse("12........34*\n...........5.");
+        assert_eq!(values(schematic.part_numbers()), vec![34, 5]);
+        assert_eq!(part2(&schematic, &GearRule::STANDARD), 170);
+    }
+
+    #[test]
+    fn test_edges_and_corners() {
+        let schematic = Schematic::parse("*1.2\n....\n7..#\n.8.9");
+        assert_eq!(values(schematic.part_numbers()), vec![1, 9]);
+
+        let schematic = Schematic::parse("9..\n.$.\n..9");
+        assert_eq!(values(schematic.part_numbers()), vec![9, 9]);
+    }
+
+    #[test]
+    fn test_ragged_lines() {
+        let schematic = Schematic::parse("..4\n*\n.5   \n\n");
+        assert_eq!(schematic.symbols.len(), 1);
+        assert_eq!(values(schematic.part_numbers()), vec![5]);
+
+        let schematic = Schematic::parse("1\n..*\n..\n22\r\n.-\r\n");
+        assert_eq!(values(schematic.part_numbers()), vec![22]);
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 const TEST_INPUT: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
 Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
 Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
@@ -8,64 +10,272 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11
 
 const INPUT: &str = include_str!("./input.txt");
 
-type Card = (Vec<i32>, Vec<i32>);
+/// Set of non-negative numbers, one bit per number below `BITSET_LIMIT`.
+/// Larger numbers are rare, and go into a hash set instead of growing the bits without bound
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct NumberSet {
+    words: Vec<u64>,
+    large: HashSet<u32>,
+}
 
-fn parse_input(input: &str) -> Vec<Card> {
-    let parse_numbers = |str: &str| -> Vec<i32> {
-        str.split(" ")
-            .filter_map(|word| word.parse().ok())
-            .collect()
-    };
+impl NumberSet {
+    const BITSET_LIMIT: u32 = 1 << 16;
+
+    fn insert(&mut self, number: u32) {
+        if number >= Self::BITSET_LIMIT {
+            self.large.insert(number);
+            return;
+        }
+        let (word, bit) = (number as usize / 64, number % 64);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << bit;
+    }
+
+    fn contains(&self, number: u32) -> bool {
+        if number >= Self::BITSET_LIMIT {
+            return self.large.contains(&number);
+        }
+        let (word, bit) = (number as usize / 64, number % 64);
+        self.words
+            .get(word)
+            .is_some_and(|&word| word & (1 << bit) != 0)
+    }
+}
+
+impl FromIterator<u32> for NumberSet {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let mut set = NumberSet::default();
+        for number in iter {
+            set.insert(number);
+        }
+        set
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Card {
+    id: usize,
+    winning: NumberSet,
+    have: Vec<u32>,
+}
+
+impl Card {
+    /// Parse a line like `Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53`
+    fn parse(line: &str) -> Option<Card> {
+        let parse_numbers = |str: &str| -> Option<Vec<u32>> {
+            str.split_whitespace()
+                .map(|word| word.parse().ok())
+                .collect()
+        };
+
+        let (header, numbers) = line.split_once(':')?;
+        let id = header.strip_prefix("Card")?.trim().parse().ok()?;
+        let (winning, have) = numbers.split_once('|')?;
+        Some(Card {
+            id,
+            winning: parse_numbers(winning)?.into_iter().collect(),
+            have: parse_numbers(have)?,
+        })
+    }
+
+    fn matching_number_count(&self) -> usize {
+        self.have
+            .iter()
+            .filter(|&&number| self.winning.contains(number))
+            .count()
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum ParseError {
+    Malformed {
+        line_number: usize,
+    },
+    /// Cards have to be numbered 1, 2, 3, ... since winning copies refers to the following cards
+    UnexpectedId {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::Malformed { line_number } => {
+                write!(f, "line {line_number}: malformed card")
+            }
+            ParseError::UnexpectedId { expected, found } => {
+                write!(f, "expected card {expected}, found card {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
 
+fn parse_input(input: &str) -> Result<Vec<Card>, ParseError> {
     input
         .lines()
-        .map(|line| {
-            let (winning_numbers, numbers) =
-                line.split_once(":").unwrap().1.split_once("|").unwrap();
-            (parse_numbers(winning_numbers), parse_numbers(numbers))
+        .enumerate()
+        .map(|(i, line)| {
+            let card = Card::parse(line).ok_or(ParseError::Malformed { line_number: i + 1 })?;
+            if card.id != i + 1 {
+                return Err(ParseError::UnexpectedId {
+                    expected: i + 1,
+                    found: card.id,
+                });
+            }
+            Ok(card)
         })
         .collect()
 }
 
-fn count_matching_numbers((winning_numbers, numbers): &Card) -> usize {
-    numbers
-        .iter()
-        .filter(|number| winning_numbers.iter().find(|n| n == number).is_some())
-        .count()
-}
-
 fn part1(cards: &[Card]) -> i32 {
     cards
         .iter()
-        .map(count_matching_numbers)
-        .filter_map(|count| (count > 0).then(|| 1 << (count - 1)))
+        .map(Card::matching_number_count)
+        .filter(|&count| count > 0)
+        .map(|count| 1 << (count - 1))
         .sum()
 }
 
-fn part2(cards: &[Card]) -> i32 {
-    let mut instances = vec![1; cards.len()];
+/// How a card ended up with its instances in the scratchcard cascade
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct CardCopies {
+    id: usize,
+    /// Copies won from earlier cards, not counting the original
+    won: u128,
+    /// `(card id, copies)` of every earlier card that won copies of this one
+    sources: Vec<(usize, u128)>,
+}
+
+impl CardCopies {
+    fn instances(&self) -> u128 {
+        self.won + 1
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct OverflowError {
+    /// The card whose instance count no longer fits
+    id: usize,
+}
+
+impl std::fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "card {}: instance count overflows u128", self.id)
+    }
+}
+
+impl std::error::Error for OverflowError {}
+
+/// Play out the cascade, where every instance of a card with `n` matching numbers wins one copy
+/// of each of the next `n` cards
+fn cascade(cards: &[Card]) -> Result<Vec<CardCopies>, OverflowError> {
+    let mut copies: Vec<CardCopies> = cards
+        .iter()
+        .map(|card| CardCopies {
+            id: card.id,
+            ..CardCopies::default()
+        })
+        .collect();
+
     for (card_num, card) in cards.iter().enumerate() {
-        let matching_number_count = count_matching_numbers(card);
-        let current_instance_count = instances[card_num];
-        for instance in instances
+        let current_instance_count = copies[card_num].instances();
+        let matching_number_count = card.matching_number_count();
+        for won in copies
             .iter_mut()
             .skip(card_num + 1)
             .take(matching_number_count)
         {
-            *instance += current_instance_count;
+            won.won = won
+                .won
+                .checked_add(current_instance_count)
+                .filter(|&won| won != u128::MAX)
+                .ok_or(OverflowError { id: won.id })?;
+            won.sources.push((card.id, current_instance_count));
         }
     }
 
-    instances.iter().sum()
+    Ok(copies)
+}
+
+fn total_instances(copies: &[CardCopies]) -> Result<u128, OverflowError> {
+    copies.iter().try_fold(0u128, |total, card| {
+        total
+            .checked_add(card.instances())
+            .ok_or(OverflowError { id: card.id })
+    })
+}
+
+fn part2(cards: &[Card]) -> u128 {
+    total_instances(&cascade(cards).unwrap()).unwrap()
 }
 
 fn main() {
-    let test_cards = parse_input(TEST_INPUT);
-    let cards = parse_input(INPUT);
+    let test_cards = parse_input(TEST_INPUT).unwrap();
+    let cards = parse_input(INPUT).unwrap();
+
+    assert_eq!(test_cards[2].id, 3);
+    assert!(test_cards[2].winning.contains(59));
+    assert!(!test_cards[2].winning.contains(69));
+    assert_eq!(test_cards[2].matching_number_count(), 2);
+    assert_eq!(
+        parse_input("Card 1: 1 | 1\nCard 3: 2 | 2").unwrap_err(),
+        ParseError::UnexpectedId {
+            expected: 2,
+            found: 3
+        }
+    );
+    assert_eq!(
+        parse_input("Card 1: 1 | 1\nCard 2: 2 x | 2").unwrap_err(),
+        ParseError::Malformed { line_number: 2 }
+    );
+    assert_eq!(
+        NumberSet::from_iter([3, 64, 1000]),
+        [1000, 64, 3].into_iter().collect()
+    );
+    let huge = Card::parse("Card 1: 4000000000 65536 | 4000000000 65535 65536").unwrap();
+    assert!(huge.winning.words.is_empty());
+    assert_eq!(huge.matching_number_count(), 2);
 
     assert_eq!(part1(&test_cards), 13);
     assert_eq!(part1(&cards), 32001);
 
     assert_eq!(part2(&test_cards), 30);
     assert_eq!(part2(&cards), 5037841);
+
+    let test_copies = cascade(&test_cards).unwrap();
+    assert_eq!(
+        test_copies[3],
+        CardCopies {
+            id: 4,
+            won: 7,
+            sources: vec![(1, 1), (2, 2), (3, 4)]
+        }
+    );
+    assert_eq!(test_copies[5].instances(), 1);
+    assert!(test_copies[5].sources.is_empty());
+
+    // Card `i` of `n` matches every following card, so it ends up with 2^(i - 1) instances
+    let doubling_pile = |n: usize| -> Vec<Card> {
+        (1..=n)
+            .map(|id| {
+                let numbers = 1..=(n - id) as u32;
+                Card {
+                    id,
+                    winning: numbers.clone().collect(),
+                    have: numbers.collect(),
+                }
+            })
+            .collect()
+    };
+    let copies = cascade(&doubling_pile(100)).unwrap();
+    assert_eq!(copies[99].instances(), 1 << 99);
+    assert_eq!(total_instances(&copies), Ok((1 << 100) - 1));
+    let copies = cascade(&doubling_pile(128)).unwrap();
+    assert_eq!(total_instances(&copies), Ok(u128::MAX));
+    assert_eq!(cascade(&doubling_pile(129)), Err(OverflowError { id: 129 }));
 }
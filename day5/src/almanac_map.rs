@@ -0,0 +1,322 @@
+#![allow(dead_code)]
+
+use crate::interval::{Interval, IntervalSet};
+
+/// `[dest_start, src_start, length]` line of an almanac map
+pub type Mapping = [isize; 3];
+
+/// Piecewise map from numbers to numbers where every piece adds a constant offset.
+///
+/// The pieces partition all of `isize`: piece `i` covers `[starts[i], starts[i + 1])` and the last
+/// piece extends to `isize::MAX`. Numbers outside of every almanac mapping are in pieces with
+/// offset 0
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AlmanacMap {
+    starts: Vec<isize>,
+    offsets: Vec<isize>,
+}
+
+impl AlmanacMap {
+    pub fn identity() -> Self {
+        AlmanacMap {
+            starts: vec![isize::MIN],
+            offsets: vec![0],
+        }
+    }
+
+    /// The map of an almanac section. Mappings should not overlap
+    pub fn new(mappings: &[Mapping]) -> Self {
+        let mut mappings = mappings.to_vec();
+        mappings.sort_by_key(|[_, src_start, _]| *src_start);
+
+        let mut pieces = vec![(isize::MIN, 0)];
+        for [dest_start, src_start, length] in mappings {
+            if length <= 0 {
+                continue;
+            }
+            pieces.push((src_start, dest_start - src_start));
+            pieces.push((src_start + length, 0));
+        }
+        AlmanacMap::from_pieces(pieces)
+    }
+
+    /// Sorts out duplicate starts (the later piece wins) and merges neighbours with equal offsets
+    fn from_pieces(pieces: Vec<(isize, isize)>) -> Self {
+        let mut starts: Vec<isize> = vec![];
+        let mut offsets: Vec<isize> = vec![];
+        for (start, offset) in pieces {
+            if starts.last() == Some(&start) {
+                starts.pop();
+                offsets.pop();
+            }
+            if offsets.last() != Some(&offset) {
+                starts.push(start);
+                offsets.push(offset);
+            }
+        }
+        AlmanacMap { starts, offsets }
+    }
+
+    /// Number of pieces, including the identity ones
+    pub fn piece_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// `(source interval, offset)` of every piece
+    pub fn pieces(&self) -> impl Iterator<Item = (Interval<isize>, isize)> + '_ {
+        (0..self.piece_count()).map(|i| (self.piece_interval(i), self.offsets[i]))
+    }
+
+    fn piece_interval(&self, i: usize) -> Interval<isize> {
+        let end = self.starts.get(i + 1).copied().unwrap_or(isize::MAX);
+        Interval::new(self.starts[i], end)
+    }
+
+    fn piece_index(&self, x: isize) -> usize {
+        self.starts.partition_point(|&start| start <= x) - 1
+    }
+
+    pub fn get(&self, x: isize) -> isize {
+        x + self.offsets[self.piece_index(x)]
+    }
+
+    /// Images of the parts of `interval`, one per piece it overlaps, in source order
+    pub fn map_interval(
+        &self,
+        interval: Interval<isize>,
+    ) -> impl Iterator<Item = Interval<isize>> + '_ {
+        let first = if interval.is_empty() {
+            self.piece_count()
+        } else {
+            self.piece_index(interval.begin)
+        };
+        (first..self.piece_count())
+            .map_while(move |i| {
+                let piece = self.piece_interval(i);
+                (piece.begin < interval.end).then(|| (piece.intersection(&interval), i))
+            })
+            .filter_map(|(part, i)| {
+                let part = part?;
+                let offset = self.offsets[i];
+                Some(Interval::new(part.begin + offset, part.end + offset))
+            })
+    }
+
+    pub fn map_set(&self, set: &IntervalSet<isize>) -> IntervalSet<isize> {
+        set.iter()
+            .flat_map(|&interval| self.map_interval(interval))
+            .collect()
+    }
+
+    /// Parts of piece `i` that map into `interval`
+    fn piece_preimage(&self, i: usize, interval: Interval<isize>) -> Option<Interval<isize>> {
+        let offset = self.offsets[i];
+        let shifted = Interval::new(
+            interval.begin.saturating_sub(offset),
+            interval.end.saturating_sub(offset),
+        );
+        self.piece_interval(i).intersection(&shifted)
+    }
+
+    /// Every number that maps into `interval`. Pieces can map onto overlapping ranges, so this can
+    /// be several intervals
+    pub fn preimage(&self, interval: Interval<isize>) -> IntervalSet<isize> {
+        (0..self.piece_count())
+            .filter_map(|i| self.piece_preimage(i, interval))
+            .collect()
+    }
+
+    /// Smallest image of a number in `domain`.
+    ///
+    /// Walks the ranges between image boundaries upward and stops at the first one that some part
+    /// of `domain` maps into
+    pub fn min_image(&self, domain: &IntervalSet<isize>) -> Option<isize> {
+        let mut boundaries: Vec<isize> = self
+            .pieces()
+            .flat_map(|(piece, offset)| {
+                [
+                    piece.begin.saturating_add(offset),
+                    piece.end.saturating_add(offset),
+                ]
+            })
+            .collect();
+        boundaries.sort_unstable();
+        boundaries.dedup();
+
+        boundaries.windows(2).find_map(|window| {
+            let locations = Interval::new(window[0], window[1]);
+            (0..self.piece_count())
+                .filter_map(|i| {
+                    let part: IntervalSet<isize> =
+                        self.piece_preimage(i, locations).into_iter().collect();
+                    domain
+                        .intersection(&part)
+                        .min()
+                        .map(|x| x + self.offsets[i])
+                })
+                .min()
+        })
+    }
+
+    /// Map equivalent to applying `self` and then `other`
+    pub fn then(&self, other: &AlmanacMap) -> AlmanacMap {
+        // The composition can only change offset where `self` does, or where `self` maps a number
+        // onto a piece boundary of `other`
+        let mut starts = self.starts.clone();
+        for i in 0..self.piece_count() {
+            let piece = self.piece_interval(i);
+            let offset = self.offsets[i];
+            let image_begin = piece.begin.saturating_add(offset);
+            let image_end = piece.end.saturating_add(offset);
+            let first = other.starts.partition_point(|&start| start <= image_begin);
+            let last = other.starts.partition_point(|&start| start < image_end);
+            starts.extend(other.starts[first..last].iter().map(|start| start - offset));
+        }
+        starts.sort_unstable();
+        starts.dedup();
+
+        let pieces = starts
+            .into_iter()
+            .map(|start| {
+                let offset = self.offsets[self.piece_index(start)];
+                let image = start.saturating_add(offset);
+                (start, offset + other.offsets[other.piece_index(image)])
+            })
+            .collect();
+        AlmanacMap::from_pieces(pieces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Mappings of a single almanac section, which never overlap
+    fn mappings() -> impl Strategy<Value = Vec<Mapping>> {
+        prop::collection::vec((0..20isize, 0..40isize, 1..8isize), 0..5).prop_map(|raw| {
+            let mut src_start = 0;
+            raw.into_iter()
+                .map(|(gap, dest_start, length)| {
+                    src_start += gap;
+                    let mapping = [dest_start, src_start, length];
+                    src_start += length;
+                    mapping
+                })
+                .collect()
+        })
+    }
+
+    /// Applies the mappings the way part 1 used to
+    fn naive_get(mappings: &[Mapping], x: isize) -> isize {
+        mappings
+            .iter()
+            .find(|[_, src_start, length]| *src_start <= x && x < src_start + length)
+            .map_or(x, |[dest_start, src_start, _]| dest_start + (x - src_start))
+    }
+
+    #[test]
+    fn test_get() {
+        let map = AlmanacMap::new(&[[50, 98, 2], [52, 50, 48]]);
+        assert_eq!(map.get(79), 81);
+        assert_eq!(map.get(98), 50);
+        assert_eq!(map.get(100), 100);
+        assert_eq!(map.get(-5), -5);
+        assert_eq!(map.piece_count(), 4);
+    }
+
+    #[test]
+    fn test_map_interval() {
+        let map = AlmanacMap::new(&[[50, 98, 2], [52, 50, 48]]);
+        let images: Vec<_> = map.map_interval(Interval::new(45, 99)).collect();
+        assert_eq!(
+            images,
+            vec![
+                Interval::new(45, 50),
+                Interval::new(52, 100),
+                Interval::new(50, 51)
+            ]
+        );
+        assert_eq!(map.map_interval(Interval::new(3, 3)).count(), 0);
+    }
+
+    #[test]
+    fn test_identity_pieces_merge() {
+        let map = AlmanacMap::new(&[[10, 0, 5], [15, 5, 5]]);
+        assert_eq!(map.piece_count(), 3);
+        // [0, 10) goes to [10, 20) and back, leaving only [10, 20) shifted
+        let composed = map.then(&AlmanacMap::new(&[[0, 10, 10]]));
+        assert_eq!(composed, AlmanacMap::new(&[[0, 10, 10]]));
+        // Swapping [0, 10) and [10, 20) twice
+        let swap = AlmanacMap::new(&[[10, 0, 10], [0, 10, 10]]);
+        assert_eq!(swap.then(&swap), AlmanacMap::identity());
+    }
+
+    #[test]
+    fn test_preimage() {
+        let map = AlmanacMap::new(&[[50, 98, 2], [52, 50, 48]]);
+        let preimage: Vec<_> = map
+            .preimage(Interval::new(49, 53))
+            .into_iter()
+            .copied()
+            .collect();
+        assert_eq!(
+            preimage,
+            vec![Interval::new(49, 51), Interval::new(98, 100)]
+        );
+        assert!(map.preimage(Interval::new(60, 60)).is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_get(mappings in mappings(), x in -5..80isize) {
+            prop_assert_eq!(AlmanacMap::new(&mappings).get(x), naive_get(&mappings, x));
+        }
+
+        #[test]
+        fn prop_then(first in mappings(), second in mappings(), x in -5..80isize) {
+            let composed = AlmanacMap::new(&first).then(&AlmanacMap::new(&second));
+            prop_assert_eq!(composed.get(x), naive_get(&second, naive_get(&first, x)));
+        }
+
+        #[test]
+        fn prop_map_interval(mappings in mappings(), begin in -5..80isize, length in 0..30isize) {
+            let map = AlmanacMap::new(&mappings);
+            let interval = Interval::from_begin_and_length(begin, length);
+            let images: IntervalSet<isize> = map.map_interval(interval).collect();
+            let expected: IntervalSet<isize> = (begin..begin + length)
+                .map(|x| Interval::from_begin_and_length(map.get(x), 1))
+                .collect();
+            prop_assert_eq!(images, expected);
+        }
+
+        #[test]
+        fn prop_preimage(first in mappings(), second in mappings(), begin in -5..80isize, length in 0..30isize) {
+            let map = AlmanacMap::new(&first).then(&AlmanacMap::new(&second));
+            let interval = Interval::from_begin_and_length(begin, length);
+            let preimage = map.preimage(interval);
+            for x in -5..80 {
+                prop_assert_eq!(preimage.contains(x), interval.contains(map.get(x)));
+            }
+        }
+
+        #[test]
+        fn prop_min_image(
+            first in mappings(),
+            second in mappings(),
+            domain in prop::collection::vec((-5..80isize, 0..10isize), 0..4),
+        ) {
+            let map = AlmanacMap::new(&first).then(&AlmanacMap::new(&second));
+            let domain: IntervalSet<isize> = domain
+                .into_iter()
+                .map(|(begin, length)| Interval::from_begin_and_length(begin, length))
+                .collect();
+            let expected = domain
+                .iter()
+                .flat_map(|interval| interval.begin..interval.end)
+                .map(|x| map.get(x))
+                .min();
+            prop_assert_eq!(map.min_image(&domain), expected);
+        }
+    }
+}
@@ -0,0 +1,323 @@
+#![allow(dead_code)]
+
+use std::ops::{Add, Sub};
+
+/// Half-open interval `[begin, end)`. Intervals with `end <= begin` are empty
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Interval<T> {
+    pub begin: T,
+    pub end: T,
+}
+
+impl<T: Copy + Ord> Interval<T> {
+    pub fn new(begin: T, end: T) -> Self {
+        Interval { begin, end }
+    }
+
+    pub fn from_begin_and_length(begin: T, length: T) -> Self
+    where
+        T: Add<Output = T>,
+    {
+        Interval::new(begin, begin + length)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.begin
+    }
+
+    pub fn len(&self) -> T
+    where
+        T: Sub<Output = T> + Default,
+    {
+        if self.is_empty() {
+            T::default()
+        } else {
+            self.end - self.begin
+        }
+    }
+
+    pub fn contains(&self, elem: T) -> bool {
+        self.begin <= elem && elem < self.end
+    }
+
+    /// Empty intervals are contained in everything
+    pub fn contains_interval(&self, other: &Interval<T>) -> bool {
+        other.is_empty() || (self.begin <= other.begin && other.end <= self.end)
+    }
+
+    pub fn intersection(&self, other: &Interval<T>) -> Option<Interval<T>> {
+        let intersection = Interval::new(self.begin.max(other.begin), self.end.min(other.end));
+        (!intersection.is_empty()).then_some(intersection)
+    }
+
+    /// The parts of `self` before and after `other`
+    pub fn difference(&self, other: &Interval<T>) -> (Option<Interval<T>>, Option<Interval<T>>) {
+        if self.is_empty() {
+            return (None, None);
+        }
+        if other.is_empty() {
+            return (Some(*self), None);
+        }
+        let before = Interval::new(self.begin, self.end.min(other.begin));
+        let after = Interval::new(self.begin.max(other.end), self.end);
+        (
+            (!before.is_empty()).then_some(before),
+            (!after.is_empty()).then_some(after),
+        )
+    }
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for Interval<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {})", self.begin, self.end)
+    }
+}
+
+/// Union of intervals, stored as sorted, disjoint and non-adjacent non-empty intervals
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet { intervals: vec![] }
+    }
+}
+
+impl<T: Copy + Ord> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Interval<T>> {
+        self.intervals.iter()
+    }
+
+    /// Smallest element of the set
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|interval| interval.begin)
+    }
+
+    pub fn contains(&self, elem: T) -> bool {
+        let index = self
+            .intervals
+            .partition_point(|interval| interval.end <= elem);
+        self.intervals
+            .get(index)
+            .is_some_and(|interval| interval.contains(elem))
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        if interval.is_empty() {
+            return;
+        }
+        // Intervals that overlap or touch `interval` form a contiguous run
+        let first = self.intervals.partition_point(|i| i.end < interval.begin);
+        let last = self.intervals.partition_point(|i| i.begin <= interval.end);
+        let merged = self.intervals[first..last]
+            .iter()
+            .fold(interval, |merged, i| {
+                Interval::new(merged.begin.min(i.begin), merged.end.max(i.end))
+            });
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.iter().chain(other.iter()).copied().collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut intervals = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (left, right) = (self.intervals[i], other.intervals[j]);
+            intervals.extend(left.intersection(&right));
+            // Whichever ends first cannot overlap anything else of the other set
+            if left.end < right.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { intervals }
+    }
+
+    /// Elements of `self` that are not in `other`
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut intervals = vec![];
+        let mut j = 0;
+        for &interval in &self.intervals {
+            let mut rest = Some(interval);
+            while let Some(current) = rest {
+                // Skip what ends before the rest of this interval
+                while j < other.intervals.len() && other.intervals[j].end <= current.begin {
+                    j += 1;
+                }
+                match other.intervals.get(j) {
+                    Some(subtrahend) if subtrahend.begin < current.end => {
+                        let (before, after) = current.difference(subtrahend);
+                        intervals.extend(before);
+                        rest = after;
+                    }
+                    _ => {
+                        intervals.push(current);
+                        rest = None;
+                    }
+                }
+            }
+        }
+        IntervalSet { intervals }
+    }
+}
+
+impl<T: Copy + Ord> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut sorted: Vec<_> = iter
+            .into_iter()
+            .filter(|interval| !interval.is_empty())
+            .collect();
+        sorted.sort_by_key(|interval| interval.begin);
+
+        let mut intervals: Vec<Interval<T>> = vec![];
+        for interval in sorted {
+            match intervals.last_mut() {
+                Some(last) if interval.begin <= last.end => last.end = last.end.max(interval.end),
+                _ => intervals.push(interval),
+            }
+        }
+        IntervalSet { intervals }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a IntervalSet<T> {
+    type Item = &'a Interval<T>;
+    type IntoIter = std::slice::Iter<'a, Interval<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.intervals.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const UNIVERSE: std::ops::Range<i32> = -4..36;
+
+    fn interval() -> impl Strategy<Value = Interval<i32>> {
+        (UNIVERSE, UNIVERSE).prop_map(|(begin, end)| Interval::new(begin, end))
+    }
+
+    fn interval_set() -> impl Strategy<Value = IntervalSet<i32>> {
+        prop::collection::vec(interval(), 0..6).prop_map(IntervalSet::from_iter)
+    }
+
+    fn elements(set: &IntervalSet<i32>) -> Vec<i32> {
+        UNIVERSE.filter(|&x| set.contains(x)).collect()
+    }
+
+    fn is_normalized(set: &IntervalSet<i32>) -> bool {
+        set.iter().all(|interval| !interval.is_empty())
+            && set
+                .intervals
+                .windows(2)
+                .all(|pair| pair[0].end < pair[1].begin)
+    }
+
+    #[test]
+    fn test_interval() {
+        let interval = Interval::from_begin_and_length(3u64, 4);
+        assert_eq!(interval, Interval::new(3, 7));
+        assert_eq!(interval.len(), 4);
+        assert_eq!(Interval::new(5u64, 2).len(), 0);
+        assert!(interval.contains(3) && !interval.contains(7));
+        assert_eq!(
+            interval.intersection(&Interval::new(6, 10)),
+            Some(Interval::new(6, 7))
+        );
+        assert_eq!(interval.intersection(&Interval::new(7, 10)), None);
+        assert_eq!(
+            interval.difference(&Interval::new(4, 5)),
+            (Some(Interval::new(3, 4)), Some(Interval::new(5, 7)))
+        );
+        assert_eq!(interval.to_string(), "[3, 7)");
+    }
+
+    #[test]
+    fn test_merge_adjacent() {
+        let set: IntervalSet<i64> = [
+            Interval::new(5, 8),
+            Interval::new(0, 2),
+            Interval::new(2, 5),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![&Interval::new(0, 8)]);
+
+        let mut set = IntervalSet::new();
+        set.insert(Interval::new(0u8, 2));
+        set.insert(Interval::new(6, 8));
+        set.insert(Interval::new(2, 6));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![&Interval::new(0, 8)]);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_interval_intersection(a in interval(), b in interval()) {
+            let expected: Vec<i32> = UNIVERSE.filter(|&x| a.contains(x) && b.contains(x)).collect();
+            let actual: Vec<i32> = UNIVERSE
+                .filter(|&x| a.intersection(&b).is_some_and(|i| i.contains(x)))
+                .collect();
+            prop_assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn prop_interval_difference(a in interval(), b in interval()) {
+            let (before, after) = a.difference(&b);
+            for x in UNIVERSE {
+                let in_difference = [before, after].iter().flatten().any(|i| i.contains(x));
+                prop_assert_eq!(in_difference, a.contains(x) && !b.contains(x));
+            }
+        }
+
+        #[test]
+        fn prop_insert_matches_collect(intervals in prop::collection::vec(interval(), 0..6)) {
+            let mut set = IntervalSet::new();
+            for &interval in &intervals {
+                set.insert(interval);
+            }
+            prop_assert!(is_normalized(&set));
+            prop_assert_eq!(set, intervals.into_iter().collect());
+        }
+
+        #[test]
+        fn prop_union(a in interval_set(), b in interval_set()) {
+            let union = a.union(&b);
+            prop_assert!(is_normalized(&union));
+            let expected: Vec<i32> = UNIVERSE.filter(|&x| a.contains(x) || b.contains(x)).collect();
+            prop_assert_eq!(elements(&union), expected);
+        }
+
+        #[test]
+        fn prop_intersection(a in interval_set(), b in interval_set()) {
+            let intersection = a.intersection(&b);
+            prop_assert!(is_normalized(&intersection));
+            let expected: Vec<i32> = UNIVERSE.filter(|&x| a.contains(x) && b.contains(x)).collect();
+            prop_assert_eq!(elements(&intersection), expected);
+        }
+
+        #[test]
+        fn prop_difference(a in interval_set(), b in interval_set()) {
+            let difference = a.difference(&b);
+            prop_assert!(is_normalized(&difference));
+            let expected: Vec<i32> = UNIVERSE.filter(|&x| a.contains(x) && !b.contains(x)).collect();
+            prop_assert_eq!(elements(&difference), expected);
+        }
+    }
+}
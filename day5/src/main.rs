@@ -1,156 +1,169 @@
-use std::ops::Range;
+mod almanac_map;
+mod interval;
+
+use almanac_map::{AlmanacMap, Mapping};
+use interval::{Interval, IntervalSet};
+use std::collections::HashSet;
 
 const TEST_INPUT: &str = include_str!("test_input.txt");
 const INPUT: &str = include_str!("input.txt");
 
 fn parse_numbers(str: &str) -> impl Iterator<Item = isize> + '_ {
-    str.split(" ").filter_map(|word| word.parse().ok())
+    str.split_whitespace().filter_map(|word| word.parse().ok())
 }
 
-fn part1(input: &str) -> isize {
-    let mut iter = input
-        .split("\r\n\r\n")
-        .flat_map(|s| s.split("\n\n")) // Line ending madness
-        .map(|s| s.split_once(":").unwrap().1.trim());
-    let seeds: Vec<isize> = parse_numbers(iter.next().unwrap()).collect();
-
-    let maps: Vec<_> = iter
-        .map(|s| {
-            s.lines()
-                .map(parse_numbers)
-                .filter_map(|numbers| -> Option<[isize; 3]> {
-                    let mut numbers = numbers.take(3);
-                    Some([numbers.next()?, numbers.next()?, numbers.next()?])
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect();
-
-    let mut location_numbers = vec![];
-    for seed in seeds {
-        let mut src = seed;
-        let mut dest = src;
-        for map in &maps {
-            dest = src;
-            for [dest_start, src_start, length] in map {
-                if src >= *src_start && src < src_start + length {
-                    dest = dest_start + (src - src_start);
-                    break;
-                }
-            }
-            src = dest;
-        }
-        location_numbers.push(dest);
-    }
-    *location_numbers.iter().min().unwrap()
+/// A `<source>-to-<destination> map:` section
+struct CategoryMap {
+    source: String,
+    destination: String,
+    map: AlmanacMap,
 }
 
-#[derive(Copy, Clone, Debug)]
-struct Interval {
-    begin: isize,
-    end: isize,
+struct Almanac {
+    seeds: Vec<isize>,
+    maps: Vec<CategoryMap>,
 }
 
-impl Interval {
-    fn from_begin_and_length(start: isize, length: isize) -> Self {
-        Interval {
-            begin: start,
-            end: start + length,
-        }
-    }
-
-    fn contains(self: &Self, elem: isize) -> bool {
-        self.begin <= elem && elem < self.end
-    }
+impl Almanac {
+    /// Rejects two sections with the same source or the same destination, so that there is at
+    /// most one chain of maps between two categories
+    fn parse(input: &str) -> Option<Almanac> {
+        let mut sections = input
+            .split("\r\n\r\n")
+            .flat_map(|s| s.split("\n\n")) // Line ending madness
+            .filter(|s| !s.trim().is_empty());
+        let seeds = parse_numbers(sections.next()?.strip_prefix("seeds:")?).collect();
+
+        let maps: Vec<CategoryMap> = sections
+            .map(|section| -> Option<CategoryMap> {
+                let (header, body) = section.trim().split_once(":")?;
+                let (source, destination) = header.strip_suffix(" map")?.split_once("-to-")?;
+                let mappings: Vec<Mapping> = body
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .map(|line| -> Option<Mapping> {
+                        let mut numbers = parse_numbers(line);
+                        let mapping = [numbers.next()?, numbers.next()?, numbers.next()?];
+                        numbers.next().is_none().then_some(mapping)
+                    })
+                    .collect::<Option<_>>()?;
+                Some(CategoryMap {
+                    source: source.to_owned(),
+                    destination: destination.to_owned(),
+                    map: AlmanacMap::new(&mappings),
+                })
+            })
+            .collect::<Option<_>>()?;
 
-    fn contains_interval(self: &Self, other: &Interval) -> bool {
-        self.begin <= other.begin && self.end >= other.end
-    }
+        let mut sources = HashSet::new();
+        let mut destinations = HashSet::new();
+        for map in &maps {
+            if !sources.insert(&map.source) || !destinations.insert(&map.destination) {
+                return None;
+            }
+        }
 
-    fn len(self: &Self) -> isize {
-        self.end - self.begin
+        Some(Almanac { seeds, maps })
     }
 
-    fn last(self: &Self) -> isize {
-        self.end - 1
+    /// The chain of maps from `source` to `destination` collapsed into a single map, following the
+    /// category names rather than the order of the sections
+    fn conversion(&self, source: &str, destination: &str) -> Option<AlmanacMap> {
+        let mut chain = AlmanacMap::identity();
+        let mut category = source;
+        // Every step uses a different map, so a chain longer than that is going in circles
+        for _ in 0..=self.maps.len() {
+            if category == destination {
+                return Some(chain);
+            }
+            let next = self.maps.iter().find(|map| map.source == category)?;
+            chain = chain.then(&next.map);
+            category = &next.destination;
+        }
+        None
     }
 
-    // fn intersection(left: &Self, right: &Self) -> Self {}
-}
-
-impl std::fmt::Display for Interval {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}, {})", self.begin, self.end)
+    fn seed_to_location(&self) -> AlmanacMap {
+        self.conversion("seed", "location").unwrap()
     }
 }
 
-fn print_mapping([dest_begin, src_begin, length]: &[isize; 3]) -> String {
-    format!(
-        "{} -> {}",
-        Interval::from_begin_and_length(*src_begin, *length),
-        Interval::from_begin_and_length(*dest_begin, *length),
-    )
+fn part1(input: &str) -> isize {
+    let almanac = Almanac::parse(input).unwrap();
+    let chain = almanac.seed_to_location();
+    almanac
+        .seeds
+        .iter()
+        .map(|&seed| chain.get(seed))
+        .min()
+        .unwrap()
 }
 
-fn part2(input: &str) -> isize {
-    let mut iter = input
-        .split("\r\n\r\n")
-        .flat_map(|s| s.split("\n\n")) // Line ending madness
-        .map(|s| s.split_once(":").unwrap().1.trim());
-    let seed_numbers: Vec<isize> = parse_numbers(iter.next().unwrap()).collect();
-    let mut seeds: Vec<_> = seed_numbers
+fn seed_ranges(seed_numbers: &[isize]) -> IntervalSet<isize> {
+    seed_numbers
         .chunks(2)
         .map(|chunk| Interval::from_begin_and_length(chunk[0], chunk[1]))
-        .collect();
-    seeds.sort_by_key(|seed| seed.begin);
-
-    for seed in &seeds {
-        print!("{seed} ");
-    }
-    println!();
-
-    let mut maps: Vec<_> = iter
-        .map(|s| {
-            s.lines()
-                .map(parse_numbers)
-                .filter_map(|numbers| -> Option<[isize; 3]> {
-                    let mut numbers = numbers.take(3);
-                    Some([numbers.next()?, numbers.next()?, numbers.next()?])
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect();
-    for map in &mut maps {
-        map.sort_by_key(|[_, src_map_begin, _]| *src_map_begin);
-    }
+        .collect()
+}
 
-    let mut srcs = seeds;
-    //let mut results = vec![];
-    let map = &maps[0];
-    for mapping in map {
-        print!("{}, ", print_mapping(mapping));
-    }
-    println!();
-
-    for src in srcs {
-        match map.binary_search_by_key(&src.begin, |[_, src_map_begin, _]| *src_map_begin) {
-            Ok(index) => println!("ok: {}", index),
-            Err(index) => {
-                if index == 0 { // Before the first
-                }
-                map[index - 1]
-            }
-        }
-    }
+fn part2(input: &str) -> isize {
+    let almanac = Almanac::parse(input).unwrap();
+    almanac
+        .seed_to_location()
+        .map_set(&seed_ranges(&almanac.seeds))
+        .min()
+        .unwrap()
+}
 
-    0
+/// Part 2 the other way around: scan locations upward until one comes from a seed
+fn part2_by_inverse(input: &str) -> isize {
+    let almanac = Almanac::parse(input).unwrap();
+    almanac
+        .seed_to_location()
+        .min_image(&seed_ranges(&almanac.seeds))
+        .unwrap()
 }
 
 fn main() {
     assert_eq!(part1(TEST_INPUT), 35);
     assert_eq!(part1(INPUT), 178159714);
 
-    part2(TEST_INPUT);
-    //assert_eq!(part2(TEST_INPUT), 46);
-    // assert_eq!(part2(INPUT), 100165128);
+    assert_eq!(part2(TEST_INPUT), 46);
+    assert_eq!(part2(INPUT), 100165128);
+    assert_eq!(part2_by_inverse(TEST_INPUT), 46);
+    assert_eq!(part2_by_inverse(INPUT), 100165128);
+
+    // Location 46 comes from seed 82
+    let test_almanac = Almanac::parse(TEST_INPUT).unwrap();
+    let location_46 = test_almanac
+        .seed_to_location()
+        .preimage(Interval::new(46, 47));
+    assert!(location_46.contains(82));
+    assert_eq!(location_46.iter().count(), 1);
+
+    // Seed 79 has soil 81, fertilizer 81, water 81, light 74, temperature 78 and humidity 78
+    let seed_to_humidity = test_almanac.conversion("seed", "humidity").unwrap();
+    assert_eq!(seed_to_humidity.get(79), 78);
+    let light_to_humidity = test_almanac.conversion("light", "humidity").unwrap();
+    assert_eq!(light_to_humidity.get(74), 78);
+    assert_eq!(
+        test_almanac.conversion("soil", "soil"),
+        Some(AlmanacMap::identity())
+    );
+    assert!(test_almanac.conversion("location", "seed").is_none());
+    assert!(test_almanac.conversion("seed", "weather").is_none());
+
+    // The chain follows the category names, so the order of the sections does not matter
+    let normalized = TEST_INPUT.replace("\r\n", "\n");
+    let mut sections: Vec<_> = normalized.trim().split("\n\n").collect();
+    sections[1..].reverse();
+    let shuffled = sections.join("\n\n");
+    assert_eq!(part1(&shuffled), 35);
+    assert_eq!(part2(&shuffled), 46);
+    assert!(Almanac::parse("seeds: 1 2\n\nseed-to-soil map:\n1 2\n").is_none());
+    // A dead end from seed would make the chain depend on which section comes first
+    let branching = format!("{}\n\nseed-to-fertilizer map:\n1 2 3", normalized.trim());
+    assert!(Almanac::parse(&branching).is_none());
+    let merging = format!("{}\n\nweather-to-water map:\n1 2 3", normalized.trim());
+    assert!(Almanac::parse(&merging).is_none());
 }
@@ -1,60 +1,166 @@
+use num_integer::Roots;
+use num_traits::{CheckedMul, Num};
+use std::ops::RangeInclusive;
+
 const TEST_INPUT: &str = "Time:      7  15   30
 Distance:  9  40  200
 ";
 
-const INPUT: &str = "Time:        38     67     76     73
-Distance:   234   1027   1157   1236";
+const INPUT: &str = include_str!("./input.txt");
 
-fn ways_to_win(time: isize, record_distance: isize) -> usize {
-    // Even bruth-force seems to be fast enough for this problem to finish instantaneously
-    // (0..=time)
-    //     .filter(|charge_time| (time - charge_time) * charge_time > record_distance)
-    //     .count()
+/// Charge times `x` in `0..=time` with `(time - x) * x > record_distance`, or `None` if no
+/// charge time beats the record.
+///
+/// Exact for any integer type, including `u128` and `BigUint`, unlike solving the quadratic with
+/// floating point. Distances too large for `T` beat any record that fits in it
+fn winning_charge_times<T>(time: &T, record_distance: &T) -> Option<RangeInclusive<T>>
+where
+    T: Clone + Ord + Num + Roots + CheckedMul,
+{
+    let two = T::one() + T::one();
+    let beats_record = |x: &T| {
+        (time.clone() - x.clone())
+            .checked_mul(x)
+            .is_none_or(|distance| distance > *record_distance)
+    };
+    let half_time = time.clone() / two.clone();
+    if !beats_record(&half_time) {
+        // Not even the best charge time beats the record
+        return None;
+    }
 
     // Root finding (x is charge_time)
-    // (time - x) * x = record_distance;
-    // x^2 - time x + record_distance = 0;
-    let a = 1.;
-    let b = -time as f64;
-    let c = record_distance as f64;
-    let delta = (b.powi(2) - (4. * a * c)).sqrt();
-    let x_1 = (-b - delta) / (2. * a);
-    let x_2 = (-b + delta) / (2. * a);
-
-    let lower_bound: usize = x_1.ceil() as usize + (x_1 == x_1.ceil()) as usize;
-    let upper_bound: usize = x_2.floor() as usize - (x_2 == x_2.floor()) as usize;
-
-    upper_bound - lower_bound + 1
+    // x^2 - time x + record_distance = 0
+    // The roots are (time ± sqrt(time^2 - 4 record_distance)) / 2
+    let time_squared = time.checked_mul(time);
+    let four_record = (two.clone() * two.clone()).checked_mul(record_distance);
+    let lower_bound = match (time_squared, four_record) {
+        (Some(time_squared), Some(four_record)) => {
+            let delta = (time_squared - four_record).sqrt();
+
+            // Rounding down delta can only make this smaller than the first winning charge time,
+            // and by less than one, so walk up to it
+            let mut lower_bound = (time.clone() - delta) / two.clone();
+            while !beats_record(&lower_bound) {
+                lower_bound = lower_bound + T::one();
+            }
+            lower_bound
+        }
+        // Too large to square, so binary search the first winning charge time instead. Distance
+        // only grows up to half the time
+        _ => {
+            let (mut low, mut high) = (T::zero(), half_time);
+            while low < high {
+                let middle = low.clone() + (high.clone() - low.clone()) / two.clone();
+                if beats_record(&middle) {
+                    high = middle;
+                } else {
+                    low = middle + T::one();
+                }
+            }
+            low
+        }
+    };
+
+    // Winning charge times are symmetric around time / 2
+    let upper_bound = time.clone() - lower_bound.clone();
+    Some(lower_bound..=upper_bound)
+}
+
+fn ways_to_win<T>(time: &T, record_distance: &T) -> T
+where
+    T: Clone + Ord + Num + Roots + CheckedMul,
+{
+    match winning_charge_times(time, record_distance) {
+        Some(range) => {
+            let (lower_bound, upper_bound) = range.into_inner();
+            upper_bound - lower_bound + T::one()
+        }
+        None => T::zero(),
+    }
+}
+
+/// A race whose best distance fits in `u128`, which `parse_races` checks
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Race {
+    time: u128,
+    record_distance: u128,
 }
 
-fn part1(input: &str) -> usize {
-    let mut lines = input.lines().map(|line| {
-        line.split_once(":")
-            .unwrap()
-            .1
-            .split_whitespace()
-            .filter_map(|token| token.parse().ok())
-            .collect::<Vec<isize>>()
-    });
-    let times = lines.next().unwrap();
-    let distances = lines.next().unwrap();
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct RaceAnalysis {
+    winning_charge_times: Option<RangeInclusive<u128>>,
+    /// Charging for half the race goes furthest (either middle value for odd times)
+    optimal_charge_time: u128,
+    /// How far the optimal charge time beats the record by, `None` if it does not reach it
+    margin: Option<u128>,
+}
+
+impl Race {
+    fn distance(&self, charge_time: u128) -> u128 {
+        (self.time - charge_time) * charge_time
+    }
+
+    fn analyze(&self) -> RaceAnalysis {
+        let optimal_charge_time = self.time / 2;
+        RaceAnalysis {
+            winning_charge_times: winning_charge_times(&self.time, &self.record_distance),
+            optimal_charge_time,
+            margin: self
+                .distance(optimal_charge_time)
+                .checked_sub(self.record_distance),
+        }
+    }
+
+    fn ways_to_win(&self) -> u128 {
+        ways_to_win(&self.time, &self.record_distance)
+    }
+}
+
+/// `Time:` and `Distance:` lines. Joins all the numbers of a line into one if `ignore_spaces`.
+/// Races that are too long for their distances to fit in `u128` are rejected
+fn parse_races(input: &str, ignore_spaces: bool) -> Option<Vec<Race>> {
+    let mut lines = input.lines().filter(|line| !line.trim().is_empty());
+    let mut parse_line = |label: &str| -> Option<Vec<u128>> {
+        let numbers = lines.next()?.strip_prefix(label)?;
+        if ignore_spaces {
+            Some(vec![numbers.replace(" ", "").parse().ok()?])
+        } else {
+            numbers
+                .split_whitespace()
+                .map(|token| token.parse().ok())
+                .collect()
+        }
+    };
+    let times = parse_line("Time:")?;
+    let distances = parse_line("Distance:")?;
+    if times.len() != distances.len() {
+        return None;
+    }
 
     times
-        .iter()
+        .into_iter()
         .zip(distances)
-        .fold(1, |acc, (&time, distance)| {
-            acc * ways_to_win(time, distance)
+        .map(|(time, record_distance)| {
+            (time / 2).checked_mul(time - time / 2)?;
+            Some(Race {
+                time,
+                record_distance,
+            })
         })
+        .collect()
 }
 
-fn part2(input: &str) -> usize {
-    let mut lines = input
-        .lines()
-        .filter_map(|line| line.split_once(":")?.1.replace(" ", "").parse().ok());
-    let time: isize = lines.next().unwrap();
-    let distance: isize = lines.next().unwrap();
+fn part1(input: &str) -> u128 {
+    parse_races(input, false)
+        .unwrap()
+        .iter()
+        .map(Race::ways_to_win)
+        .product()
+}
 
-    ways_to_win(time, distance)
+fn part2(input: &str) -> u128 {
+    parse_races(input, true).unwrap()[0].ways_to_win()
 }
 
 fn main() {
@@ -62,4 +168,153 @@ fn main() {
     assert_eq!(part1(INPUT), 303600);
     assert_eq!(part2(TEST_INPUT), 71503);
     assert_eq!(part2(INPUT), 23654842);
+
+    let races = parse_races(TEST_INPUT, false).unwrap();
+    assert_eq!(
+        races[0].analyze(),
+        RaceAnalysis {
+            winning_charge_times: Some(2..=5),
+            optimal_charge_time: 3,
+            margin: Some(3)
+        }
+    );
+    assert_eq!(races[2].analyze().winning_charge_times, Some(11..=19));
+    assert_eq!(races[2].analyze().margin, Some(25));
+    let unbeatable = Race {
+        time: 6,
+        record_distance: 9,
+    };
+    assert_eq!(
+        unbeatable.analyze(),
+        RaceAnalysis {
+            winning_charge_times: None,
+            optimal_charge_time: 3,
+            margin: Some(0)
+        }
+    );
+    assert_eq!(
+        parse_races(TEST_INPUT, true),
+        Some(vec![Race {
+            time: 71530,
+            record_distance: 940200
+        }])
+    );
+    assert_eq!(parse_races("Time: 7 15\nDistance: 9", false), None);
+    let long_race = format!("Time: {}\nDistance: 0", 1u128 << 65);
+    assert_eq!(parse_races(&long_race, false), None);
+
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(path) = args.iter().skip_while(|&arg| arg != "--races").nth(1) {
+        let input = std::fs::read_to_string(path).expect("Race file should be readable");
+        let races = parse_races(&input, args.iter().any(|arg| arg == "--ignore-spaces"))
+            .expect("Race file should have a Time: and a Distance: line");
+        for race in races {
+            let analysis = race.analyze();
+            match analysis.winning_charge_times {
+                Some(range) => println!(
+                    "{}ms, record {}mm: charge {}..={}ms, best {}ms beats the record by {}mm",
+                    race.time,
+                    race.record_distance,
+                    range.start(),
+                    range.end(),
+                    analysis.optimal_charge_time,
+                    analysis.margin.unwrap()
+                ),
+                None => println!(
+                    "{}ms, record {}mm: cannot be won",
+                    race.time, race.record_distance
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+    use proptest::prelude::*;
+
+    fn brute_force(time: u64, record_distance: u64) -> u64 {
+        (0..=time)
+            .filter(|charge_time| (time - charge_time) * charge_time > record_distance)
+            .count() as u64
+    }
+
+    #[test]
+    fn test_ways_to_win() {
+        assert_eq!(ways_to_win(&7u32, &9), 4);
+        assert_eq!(ways_to_win(&30u32, &200), 9);
+        // The record is exactly the best distance
+        assert_eq!(ways_to_win(&6u32, &9), 0);
+        assert_eq!(ways_to_win(&6u32, &8), 1);
+        assert_eq!(ways_to_win(&0u32, &0), 0);
+        assert_eq!(ways_to_win(&1u32, &0), 0);
+        assert_eq!(ways_to_win(&2u32, &0), 1);
+    }
+
+    #[test]
+    fn test_beyond_f64_precision() {
+        // An odd time reaches the best distance with the two middle charge times only
+        let time: u128 = (1 << 60) + 1;
+        let best = (time / 2) * (time - time / 2);
+        assert_eq!(ways_to_win(&time, &(best - 1)), 2);
+        assert_eq!(ways_to_win(&time, &best), 0);
+
+        let big_time: BigUint = BigUint::from(time) << 100u32;
+        let big_best = (big_time.clone() / 2u32) * (big_time.clone() / 2u32);
+        assert_eq!(
+            ways_to_win(&big_time, &(big_best.clone() - 1u32)),
+            BigUint::from(1u32)
+        );
+        assert_eq!(
+            ways_to_win(&big_time, &(big_best - 2u32)),
+            BigUint::from(3u32)
+        );
+    }
+
+    #[test]
+    fn test_beyond_u128_squares() {
+        // time^2 is just past u128::MAX, but the best distance 2^126 fits
+        let time: u128 = 1 << 64;
+        let best = 1 << 126;
+        assert_eq!(ways_to_win(&time, &(best - 1)), 1);
+        assert_eq!(ways_to_win(&time, &best), 0);
+        // 4 record_distance overflows
+        assert_eq!(ways_to_win(&time, &u128::MAX), 0);
+
+        let agree = |time: u128, record_distance: u128| {
+            assert_eq!(
+                BigUint::from(ways_to_win(&time, &record_distance)),
+                ways_to_win(&BigUint::from(time), &BigUint::from(record_distance)),
+                "{time} {record_distance}"
+            );
+        };
+        agree(u128::MAX, u128::MAX);
+        agree(u128::MAX, 0);
+        agree(u128::MAX - 1, u128::MAX);
+        agree(1 << 64, (1 << 126) - 1);
+        agree((1 << 65) + 1, u128::MAX / 4 + 1);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matches_brute_force(time in 0..2000u64, record_fraction in 0.0..1.1f64) {
+            let record_distance = ((time * time / 4) as f64 * record_fraction) as u64;
+            prop_assert_eq!(ways_to_win(&time, &record_distance), brute_force(time, record_distance));
+        }
+
+        #[test]
+        fn prop_types_agree(time in any::<u128>(), record in any::<u128>()) {
+            // Keep the record around the best distance when that fits
+            let record_distance = (time / 2)
+                .checked_mul(time - time / 2)
+                .map_or(record, |best| record % best.saturating_add(2));
+            let expected = ways_to_win(&time, &record_distance);
+            prop_assert_eq!(
+                ways_to_win(&BigUint::from(time), &BigUint::from(record_distance)),
+                BigUint::from(expected)
+            );
+        }
+    }
 }
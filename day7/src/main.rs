@@ -1,5 +1,3 @@
-use std::cmp::Ordering;
-
 const TEST_INPUT: &str = "32T3K 765
 T55J5 684
 KK677 28
@@ -8,42 +6,133 @@ QQQJA 483";
 
 const INPUT: &str = include_str!("input.txt");
 
-fn label_to_strength<const IS_PART2: bool>(label: u8) -> isize {
-    let labels = if IS_PART2 {
-        [
-            b'J', b'2', b'3', b'4', b'5', b'6', b'7', b'8', b'9', b'T', b'Q', b'K', b'A',
-        ]
-    } else {
-        [
-            b'2', b'3', b'4', b'5', b'6', b'7', b'8', b'9', b'T', b'J', b'Q', b'K', b'A',
-        ]
-    };
-    labels.iter().position(|x| x == &label).unwrap() as isize
+/// How a variant of Camel Cards is played
+#[derive(Clone, Debug)]
+struct RuleSet {
+    /// Card labels from the weakest to the strongest
+    order: Vec<u8>,
+    /// Labels that act like whatever card makes the best kind
+    wild: Vec<u8>,
+    hand_size: usize,
+    /// Bits per card strength in the sort key
+    strength_bits: u32,
+}
+
+impl RuleSet {
+    /// `None` if a label repeats, a wild label is not in `order`, or hands would not fit in a
+    /// sort key
+    fn new(order: &str, wild: &str, hand_size: usize) -> Option<RuleSet> {
+        let order = order.as_bytes().to_vec();
+        let wild = wild.as_bytes().to_vec();
+        let unique = |labels: &[u8]| {
+            labels
+                .iter()
+                .enumerate()
+                .all(|(i, label)| !labels[..i].contains(label))
+        };
+        if order.is_empty() || hand_size == 0 || !unique(&order) || !unique(&wild) {
+            return None;
+        }
+        if !wild.iter().all(|label| order.contains(label)) {
+            return None;
+        }
+
+        let bits_for = |max: usize| (usize::BITS - max.leading_zeros()).max(1);
+        let strength_bits = bits_for(order.len() - 1);
+        // The kind keeps group sizes in 4 bits, and the cards share the lower half of the sort key
+        if hand_size > 15 || strength_bits as usize * hand_size > u64::BITS as usize {
+            return None;
+        }
+
+        Some(RuleSet {
+            order,
+            wild,
+            hand_size,
+            strength_bits,
+        })
+    }
+
+    /// Part 1
+    fn standard() -> RuleSet {
+        RuleSet::new("23456789TJQKA", "", 5).unwrap()
+    }
+
+    /// Part 2: `J` is a joker, and the weakest card on its own
+    fn jokers() -> RuleSet {
+        RuleSet::new("J23456789TQKA", "J", 5).unwrap()
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Card {
+    label: u8,
+    /// 0 for the weakest card of the rule set
+    strength: u8,
+    wild: bool,
+}
+
+impl Card {
+    fn parse(label: u8, rules: &RuleSet) -> Option<Card> {
+        let strength = rules.order.iter().position(|&x| x == label)?;
+        Some(Card {
+            label,
+            strength: strength as u8,
+            wild: rules.wild.contains(&label),
+        })
+    }
+
+    fn label(self) -> char {
+        self.label as char
+    }
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq)]
-struct HandKind(isize);
+/// Sizes of the groups of equal cards from the largest, one per 4 bits from the most significant
+/// ones, so that comparing kinds compares the largest groups first
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+struct HandKind(u64);
 
 impl HandKind {
-    const FIVE_OF_A_KIND: HandKind = HandKind(6);
-    const FOUR_OF_A_KIND: HandKind = HandKind(5);
-    const FULL_HOUSE: HandKind = HandKind(4);
-    const THREE_OF_A_KIND: HandKind = HandKind(3);
-    const TWO_PAIRS: HandKind = HandKind(2);
-    const ONE_PAIR: HandKind = HandKind(1);
-    const HIGH_CARD: HandKind = HandKind(0);
+    const FIVE_OF_A_KIND: HandKind = HandKind::from_groups(&[5]);
+    const FOUR_OF_A_KIND: HandKind = HandKind::from_groups(&[4, 1]);
+    const FULL_HOUSE: HandKind = HandKind::from_groups(&[3, 2]);
+    const THREE_OF_A_KIND: HandKind = HandKind::from_groups(&[3, 1, 1]);
+    const TWO_PAIRS: HandKind = HandKind::from_groups(&[2, 2, 1]);
+    const ONE_PAIR: HandKind = HandKind::from_groups(&[2, 1, 1, 1]);
+    const HIGH_CARD: HandKind = HandKind::from_groups(&[1, 1, 1, 1, 1]);
+
+    /// Group sizes should be sorted from the largest, with at most 15 cards in total
+    const fn from_groups(groups: &[u8]) -> HandKind {
+        let mut packed = 0;
+        let mut i = 0;
+        while i < groups.len() {
+            packed |= (groups[i] as u64) << (60 - 4 * i);
+            i += 1;
+        }
+        HandKind(packed)
+    }
+
+    fn groups(self) -> impl Iterator<Item = u8> {
+        (0..16)
+            .map(move |i| ((self.0 >> (60 - 4 * i)) & 0xF) as u8)
+            .take_while(|&group| group > 0)
+    }
 }
 
 impl std::fmt::Display for HandKind {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match self.0 {
-            6 => write!(f, "Five of a kind"),
-            5 => write!(f, "Four of a kind"),
-            4 => write!(f, "Full House"),
-            3 => write!(f, "Three of a Kind"),
-            2 => write!(f, "Two Pairs"),
-            1 => write!(f, "One Pair"),
-            _ => write!(f, "High Card"),
+        match *self {
+            HandKind::FIVE_OF_A_KIND => write!(f, "Five of a kind"),
+            HandKind::FOUR_OF_A_KIND => write!(f, "Four of a kind"),
+            HandKind::FULL_HOUSE => write!(f, "Full House"),
+            HandKind::THREE_OF_A_KIND => write!(f, "Three of a Kind"),
+            HandKind::TWO_PAIRS => write!(f, "Two Pairs"),
+            HandKind::ONE_PAIR => write!(f, "One Pair"),
+            _ if self.groups().all(|group| group == 1) => write!(f, "High Card"),
+            // Other hand sizes, e.g. `4+2`
+            _ => {
+                let groups: Vec<_> = self.groups().map(|group| group.to_string()).collect();
+                write!(f, "{}", groups.join("+"))
+            }
         }
     }
 }
@@ -53,128 +142,309 @@ impl std::fmt::Debug for HandKind {
     }
 }
 
-fn hand_kind<const IS_PART2: bool>(hand: &[u8; 5]) -> HandKind {
-    let mut label_counts: [isize; 13] = [0; 13];
-    for c in hand {
-        let single_label_strength = label_to_strength::<IS_PART2>(*c);
-        label_counts[single_label_strength as usize] += 1;
-    }
-
-    let mut count_of_counts: [usize; 6] = Default::default();
-    for (label, count) in label_counts.iter().enumerate() {
-        /* exclude j (label = 0 here) for part 2 */
-        if *count > 0 && (!IS_PART2 || label != 0) {
-            count_of_counts[*count as usize] += 1;
+/// The best kind of `cards`. Wild cards always do best by joining the largest group of other
+/// cards, so they are counted as that card
+fn hand_kind(cards: &[Card], rules: &RuleSet) -> HandKind {
+    let mut label_counts = vec![0u8; rules.order.len()];
+    let mut wild_count = 0;
+    for card in cards {
+        if card.wild {
+            wild_count += 1;
+        } else {
+            label_counts[card.strength as usize] += 1;
         }
     }
 
-    let no_joker_hand_kind = || {
-        if count_of_counts[5] == 1 {
-            HandKind::FIVE_OF_A_KIND
-        } else if count_of_counts[4] == 1 {
-            HandKind::FOUR_OF_A_KIND
-        } else if count_of_counts[3] == 1 {
-            if count_of_counts[2] == 1 {
-                HandKind::FULL_HOUSE
-            } else {
-                HandKind::THREE_OF_A_KIND
-            }
-        } else if count_of_counts[2] == 2 {
-            HandKind::TWO_PAIRS
-        } else if count_of_counts[2] == 1 {
-            HandKind::ONE_PAIR
-        } else {
-            HandKind::HIGH_CARD
-        }
-    };
-
-    if !IS_PART2 {
-        no_joker_hand_kind()
-    } else {
-        let j_count = label_counts[0];
-
-        if j_count == 0 {
-            no_joker_hand_kind()
-        } else if j_count == 1 {
-            if count_of_counts[4] == 1 {
-                HandKind::FIVE_OF_A_KIND
-            } else if count_of_counts[3] == 1 {
-                HandKind::FOUR_OF_A_KIND
-            } else if count_of_counts[2] == 2 {
-                HandKind::FULL_HOUSE
-            } else if count_of_counts[2] == 1 {
-                HandKind::THREE_OF_A_KIND
-            } else {
-                HandKind::ONE_PAIR
-            }
-        } else if j_count == 2 {
-            if count_of_counts[3] == 1 {
-                HandKind::FIVE_OF_A_KIND
-            } else if count_of_counts[2] == 1 {
-                HandKind::FOUR_OF_A_KIND
-            } else {
-                HandKind::THREE_OF_A_KIND
-            }
-        } else if j_count == 3 {
-            if count_of_counts[2] == 1 {
-                HandKind::FIVE_OF_A_KIND
-            } else {
-                HandKind::FOUR_OF_A_KIND
-            }
-        } else {
-            HandKind::FIVE_OF_A_KIND
+    label_counts.sort_unstable_by(|a, b| b.cmp(a));
+    label_counts[0] += wild_count;
+    let groups: Vec<u8> = label_counts
+        .into_iter()
+        .take_while(|&count| count > 0)
+        .collect();
+    HandKind::from_groups(&groups)
+}
+
+struct Hand {
+    cards: Vec<Card>,
+    bid: isize,
+    kind: HandKind,
+    /// Orders hands by kind and then card by card, computed once when parsing
+    sort_key: u128,
+}
+
+impl Hand {
+    /// Parse a line like `32T3K 765`
+    fn parse(line: &str, rules: &RuleSet) -> Option<Hand> {
+        let (cards, bid) = line.split_once(' ')?;
+        let cards: Vec<Card> = cards
+            .bytes()
+            .map(|label| Card::parse(label, rules))
+            .collect::<Option<_>>()?;
+        if cards.len() != rules.hand_size {
+            return None;
         }
+        let bid = bid.trim().parse().ok()?;
+
+        let kind = hand_kind(&cards, rules);
+        let card_key = cards.iter().fold(0, |key, card| {
+            (key << rules.strength_bits) | card.strength as u128
+        });
+        let sort_key = ((kind.0 as u128) << u64::BITS) | card_key;
+
+        Some(Hand {
+            cards,
+            bid,
+            kind,
+            sort_key,
+        })
     }
 }
 
-fn compare_hand_strength<const IS_PART2: bool>(hand1: &[u8; 5], hand2: &[u8; 5]) -> Ordering {
-    match hand_kind::<IS_PART2>(hand1).cmp(&hand_kind::<IS_PART2>(hand2)) {
-        Ordering::Equal => {
-            for (&c1, &c2) in hand1.iter().zip(hand2) {
-                match label_to_strength::<IS_PART2>(c1).cmp(&label_to_strength::<IS_PART2>(c2)) {
-                    Ordering::Equal => {}
-                    greater_or_less => return greater_or_less,
-                }
-            }
-            Ordering::Equal
-        }
-        greater_or_less => greater_or_less,
+/// A line that isn't a hand of the rule set followed by a bid
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct ParseError {
+    line_number: usize,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: invalid hand", self.line_number)
     }
 }
 
-fn common<const IS_PART2: bool>(input: &str) -> isize {
-    let mut hands: Vec<([u8; 5], isize)> = input
+impl std::error::Error for ParseError {}
+
+fn parse_hands(input: &str, rules: &RuleSet) -> Result<Vec<Hand>, ParseError> {
+    input
         .lines()
-        .filter_map(|line| line.split_once(" "))
-        .map(|(hand, bid)| {
-            (
-                hand.as_bytes().try_into().unwrap(),
-                bid.parse::<isize>().unwrap(),
-            )
-        })
-        .collect();
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| Hand::parse(line, rules).ok_or(ParseError { line_number: i + 1 }))
+        .collect()
+}
 
-    hands.sort_by(|(hand1, _), (hand2, _)| compare_hand_strength::<IS_PART2>(hand1, hand2));
+/// Hands from the weakest to the strongest, so the rank of `hands[i]` is `i + 1`
+fn ranked_hands(input: &str, rules: &RuleSet) -> Result<Vec<Hand>, ParseError> {
+    let mut hands = parse_hands(input, rules)?;
+    hands.sort_unstable_by_key(|hand| hand.sort_key);
+    Ok(hands)
+}
+
+fn total_winnings(input: &str, rules: &RuleSet) -> Result<isize, ParseError> {
+    let hands = ranked_hands(input, rules)?;
 
     let mut winning = 0;
-    for (i, (_, bid)) in hands.iter().enumerate() {
-        winning += (i as isize + 1) * bid;
+    for (i, hand) in hands.iter().enumerate() {
+        winning += (i as isize + 1) * hand.bid;
+    }
+    Ok(winning)
+}
+
+/// The card that wild cards stand for: the most common other card, the strongest one on ties.
+/// `None` if the hand has no wild cards or nothing but wild cards
+fn wild_substitute(cards: &[Card]) -> Option<Card> {
+    if !cards.iter().any(|card| card.wild) {
+        return None;
+    }
+    cards
+        .iter()
+        .filter(|card| !card.wild)
+        .max_by_key(|card| {
+            let count = cards
+                .iter()
+                .filter(|other| other.label == card.label)
+                .count();
+            (count, card.strength)
+        })
+        .copied()
+}
+
+/// Why a hand ended up where it did
+struct Explanation {
+    cards: String,
+    bid: isize,
+    /// Kind when wild cards only count as themselves
+    natural_kind: HandKind,
+    kind: HandKind,
+    substitute: Option<Card>,
+    rank: usize,
+    winnings: isize,
+}
+
+impl std::fmt::Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (bid {}): {}",
+            self.cards, self.bid, self.natural_kind
+        )?;
+        if let Some(substitute) = self.substitute {
+            write!(f, " -> {} as {}", self.kind, substitute.label())?;
+        }
+        write!(f, ", rank {}, wins {}", self.rank, self.winnings)
     }
-    winning
 }
 
-fn part1(input: &str) -> isize {
-    common::<false>(input)
+/// Every hand of `input` from the weakest to the strongest
+fn explain(input: &str, rules: &RuleSet) -> Result<Vec<Explanation>, ParseError> {
+    let explanations = ranked_hands(input, rules)?
+        .iter()
+        .enumerate()
+        .map(|(i, hand)| {
+            let natural_cards: Vec<Card> = hand
+                .cards
+                .iter()
+                .map(|&card| Card {
+                    wild: false,
+                    ..card
+                })
+                .collect();
+            Explanation {
+                cards: hand.cards.iter().map(|card| card.label()).collect(),
+                bid: hand.bid,
+                natural_kind: hand_kind(&natural_cards, rules),
+                kind: hand.kind,
+                substitute: wild_substitute(&hand.cards),
+                rank: i + 1,
+                winnings: (i as isize + 1) * hand.bid,
+            }
+        })
+        .collect();
+    Ok(explanations)
 }
 
-fn part2(input: &str) -> isize {
-    common::<true>(input)
+fn part1(input: &str) -> Result<isize, ParseError> {
+    total_winnings(input, &RuleSet::standard())
+}
+
+fn part2(input: &str) -> Result<isize, ParseError> {
+    total_winnings(input, &RuleSet::jokers())
 }
 
 fn main() {
-    assert_eq!(part1(TEST_INPUT), 6440);
-    assert_eq!(part1(INPUT), 246912307);
+    assert_eq!(part1(TEST_INPUT), Ok(6440));
+    assert_eq!(part1(INPUT), Ok(246912307));
+
+    assert_eq!(part2(TEST_INPUT), Ok(5905));
+    assert_eq!(part2(INPUT), Ok(246894760));
+
+    let standard = RuleSet::standard();
+    let jokers = RuleSet::jokers();
+    assert_eq!(Card::parse(b'T', &standard).map(Card::label), Some('T'));
+    assert_eq!(Card::parse(b'1', &standard), None);
+    assert_eq!(Card::parse(b'J', &standard).unwrap().strength, 9);
+    assert_eq!(Card::parse(b'J', &jokers).unwrap().strength, 0);
+    assert_eq!(Card::parse(b'Q', &jokers).unwrap().strength, 10);
+
+    let hands = parse_hands(TEST_INPUT, &jokers).unwrap();
+    assert_eq!(hands[1].kind, HandKind::FOUR_OF_A_KIND);
+    assert_eq!(hands[1].bid, 684);
+    assert_eq!(hands[1].cards[3].label(), 'J');
+    assert!(hands[1].sort_key < hands[4].sort_key);
+    assert!(Hand::parse("32T3 765", &standard).is_none());
+    assert!(Hand::parse("32T3KK 765", &standard).is_none());
+    assert!(Hand::parse("32T3X 765", &standard).is_none());
+    assert!(Hand::parse("32T3K", &standard).is_none());
+    assert_eq!(
+        parse_hands("32T3K 765\n\nT55J5 x", &standard).err(),
+        Some(ParseError { line_number: 3 })
+    );
+    assert_eq!(
+        part1("32T3K 765\n32T3 765").unwrap_err().to_string(),
+        "line 2: invalid hand"
+    );
 
-    assert_eq!(part2(TEST_INPUT), 5905);
-    assert_eq!(part2(INPUT), 246894760);
+    let kind = |hand: &str, rules: &RuleSet| Hand::parse(&format!("{hand} 1"), rules).unwrap().kind;
+    assert_eq!(kind("JJJJJ", &jokers), HandKind::FIVE_OF_A_KIND);
+    assert_eq!(kind("2345J", &jokers), HandKind::ONE_PAIR);
+    assert_eq!(kind("2233J", &jokers), HandKind::FULL_HOUSE);
+    assert_eq!(kind("2345J", &standard), HandKind::HIGH_CARD);
+    assert_eq!(HandKind::ONE_PAIR.to_string(), "One Pair");
+
+    // Twos are wild as well as jokers
+    let two_wild = RuleSet::new("J2TQKA", "J2", 5).unwrap();
+    assert_eq!(kind("JT2QK", &two_wild), HandKind::THREE_OF_A_KIND);
+    assert_eq!(kind("J2JTT", &two_wild), HandKind::FIVE_OF_A_KIND);
+    // All one pair, so the wild J is the weakest first card and T the strongest
+    assert_eq!(
+        total_winnings("2TQKA 1\nJTQKA 2\nTTQKA 3", &two_wild),
+        Ok(2 + 2 + 3 * 3)
+    );
+
+    // Six card hands
+    let six_cards = RuleSet::new("23456789TJQKA", "", 6).unwrap();
+    assert_eq!(kind("222222", &six_cards).to_string(), "6");
+    assert_eq!(kind("222233", &six_cards).to_string(), "4+2");
+    assert_eq!(kind("223344", &six_cards).to_string(), "2+2+2");
+    assert_eq!(kind("23456A", &six_cards).to_string(), "High Card");
+    assert!(kind("223344", &six_cards) > kind("223345", &six_cards));
+    assert!(kind("222333", &six_cards) > kind("222334", &six_cards));
+    assert!(kind("222333", &six_cards) < kind("222234", &six_cards));
+    // 22233Q (3+2+1) < 222333 (3+3) < 2222AK (4+1+1)
+    assert_eq!(
+        total_winnings("222333 1\n22233Q 10\n2222AK 100", &six_cards),
+        Ok(10 + 2 + 300)
+    );
+
+    assert!(RuleSet::new("2234", "", 5).is_none());
+    assert!(RuleSet::new("234", "J", 5).is_none());
+    assert!(RuleSet::new("23456789TJQKA", "", 16).is_none());
+    assert!(RuleSet::new("23456789TJQKA", "", 15).is_some());
+
+    let explanations = explain(TEST_INPUT, &jokers).unwrap();
+    assert_eq!(
+        Ok(explanations.iter().map(|e| e.winnings).sum::<isize>()),
+        part2(TEST_INPUT)
+    );
+    let ktjjt = &explanations[4];
+    assert_eq!(ktjjt.cards, "KTJJT");
+    assert_eq!(ktjjt.natural_kind, HandKind::TWO_PAIRS);
+    assert_eq!(ktjjt.kind, HandKind::FOUR_OF_A_KIND);
+    assert_eq!(ktjjt.substitute.map(Card::label), Some('T'));
+    assert_eq!(
+        ktjjt.to_string(),
+        "KTJJT (bid 220): Two Pairs -> Four of a kind as T, rank 5, wins 1100"
+    );
+    assert_eq!(
+        explanations[0].to_string(),
+        "32T3K (bid 765): One Pair, rank 1, wins 765"
+    );
+    let explanation = &explain("JJJJJ 3", &jokers).unwrap()[0];
+    assert_eq!(explanation.substitute, None);
+    assert_eq!(
+        explanation.to_string(),
+        "JJJJJ (bid 3): Five of a kind, rank 1, wins 3"
+    );
+    assert_eq!(
+        explain("QJJQ2 2", &jokers).unwrap()[0].to_string(),
+        "QJJQ2 (bid 2): Two Pairs -> Four of a kind as Q, rank 1, wins 2"
+    );
+
+    let args: Vec<String> = std::env::args().collect();
+    if args.iter().any(|arg| arg == "--explain") {
+        let input = if args.iter().any(|arg| arg == "--test") {
+            TEST_INPUT
+        } else {
+            INPUT
+        };
+        let rules = if args.iter().any(|arg| arg == "--jokers") {
+            jokers
+        } else {
+            standard
+        };
+        let explanations = match explain(input, &rules) {
+            Ok(explanations) => explanations,
+            Err(error) => {
+                eprintln!("{error}");
+                return;
+            }
+        };
+        for explanation in &explanations {
+            println!("{explanation}");
+        }
+        println!(
+            "Total winnings: {}",
+            explanations.iter().map(|e| e.winnings).sum::<isize>()
+        );
+    }
 }